@@ -1,8 +1,269 @@
 use core::{
-    fmt::{self, Debug, Formatter},
+    fmt::{self, Debug, Display, Formatter},
     ops::Range,
 };
 
+/// Errors reported by GIC driver operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GicError {
+    /// A non-zero affinity level 3 was requested, but the GIC does not
+    /// implement affinity level 3 (`A3V` is clear).
+    Affinity3NotSupported,
+    /// The operation only applies to shared interrupts (SPIs), but a private
+    /// interrupt (SGI/PPI) was given.
+    PrivateInterrupt(IntId),
+    /// Deactivation through the DIR register was requested while the CPU
+    /// interface is in single-step EOI mode (`EOImode` is clear).
+    NotTwoStepEoiMode,
+    /// 1-of-N SPI routing was requested, but the GIC does not support it
+    /// (`No1N` is set).
+    OneOfNNotSupported,
+    /// SGIs without an active state were requested, but the distributor
+    /// does not support them (`GICD_TYPER2.nASSGIcap` is clear).
+    NassgiNotSupported,
+    /// The interrupt must be an SPI.
+    NotSpi(IntId),
+    /// The interrupt is already present in the remapping table.
+    AlreadyMapped(IntId),
+    /// The interrupt has no entry in the remapping table.
+    NotMapped(IntId),
+    /// The remapping table has no free entry.
+    RemapTableFull,
+    /// An SGI target with an affinity level 0 value of 16 or above was
+    /// requested, but the CPU interface does not support the range selector
+    /// (`RSS` is clear).
+    RangeSelectorNotSupported,
+    /// The GIC did not complete a handshake in time, e.g. the redistributor
+    /// power or `GICR_WAKER` handshake, or draining pending interrupts.
+    Timeout,
+    /// A register write did not complete in time (`GICD_CTLR.RWP` or
+    /// `GICR_CTLR.RWP` stayed set).
+    RwpTimeout,
+    /// A redistributor reports an affinity the distributor cannot route to,
+    /// e.g. a non-zero `Aff3` while `GICD_TYPER.A3V` is clear. Holds the raw
+    /// `GICR_TYPER.Affinity` value (`Aff3.Aff2.Aff1.Aff0`).
+    AffinityMismatch(u32),
+    /// Every implemented list register already holds a virtual interrupt.
+    ListRegistersFull,
+    /// The list register index is not below the number of implemented list
+    /// registers (`ListRegs + 1`).
+    InvalidListRegister(usize),
+    /// The physical interrupt ID does not fit the list register's physical
+    /// ID field.
+    PhysicalIdOutOfRange(IntId),
+    /// An SGI target list mixes CPUs with different `Aff3.Aff2.Aff1`
+    /// values; one SGI can only address CPUs that differ in `Aff0`. Holds the
+    /// first offending affinity (`Aff3.Aff2.Aff1.Aff0`).
+    MixedTargetAffinity(u32),
+    /// The interrupt ID lies in an architected range that this GIC does not
+    /// implement, e.g. an Extended PPI above GICR_TYPER.PPInum.
+    NotImplemented(IntId),
+    /// No redistributor has the requested affinity, so an interrupt routed
+    /// there would never be delivered. Holds the affinity as
+    /// `Aff3.Aff2.Aff1.Aff0`.
+    UnknownAffinity(u32),
+    /// The operation uses a legacy register that is only accessible with
+    /// affinity routing disabled (`GICD_CTLR.ARE` clear), but it is enabled.
+    AffinityRoutingEnabled,
+    /// The mapped redistributor region ends before a redistributor with
+    /// `GICR_TYPER.Last` set was found.
+    RedistributorRegionTooSmall,
+    /// The CPU interface number is not below the number of implemented CPU
+    /// interfaces (`GICD_TYPER.CPUNumber + 1`).
+    InvalidCpuInterface(usize),
+    /// Secure Group 1 was requested, but the GIC is not accessed from the
+    /// Secure state, where GICD_IGRPMODR is RAZ/WI.
+    SecureGroupUnavailable,
+}
+
+impl Display for GicError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GicError::Affinity3NotSupported => {
+                write!(f, "affinity level 3 is not supported by this GIC")
+            }
+            GicError::PrivateInterrupt(id) => {
+                write!(f, "operation not supported for private interrupt {id:?}")
+            }
+            GicError::NotTwoStepEoiMode => {
+                write!(f, "interrupt deactivation requires two-step EOI mode")
+            }
+            GicError::OneOfNNotSupported => {
+                write!(f, "1-of-N interrupt routing is not supported by this GIC")
+            }
+            GicError::NassgiNotSupported => {
+                write!(f, "SGIs without active state are not supported by this GIC")
+            }
+            GicError::NotSpi(id) => write!(f, "{id:?} is not an SPI"),
+            GicError::AlreadyMapped(id) => write!(f, "{id:?} is already mapped"),
+            GicError::NotMapped(id) => write!(f, "{id:?} is not mapped"),
+            GicError::RemapTableFull => write!(f, "interrupt remapping table is full"),
+            GicError::RangeSelectorNotSupported => {
+                write!(
+                    f,
+                    "SGI range selector is not supported by this CPU interface"
+                )
+            }
+            GicError::Timeout => write!(f, "timed out waiting for the GIC"),
+            GicError::RwpTimeout => {
+                write!(f, "timed out waiting for a register write to complete")
+            }
+            GicError::ListRegistersFull => write!(f, "no free list register"),
+            GicError::InvalidListRegister(idx) => {
+                write!(f, "list register {idx} is not implemented")
+            }
+            GicError::PhysicalIdOutOfRange(id) => {
+                write!(f, "{id:?} does not fit the list register physical ID field")
+            }
+            GicError::AffinityMismatch(aff) => {
+                write!(
+                    f,
+                    "redistributor affinity {aff:#010x} is not routable by the distributor"
+                )
+            }
+            GicError::MixedTargetAffinity(aff) => {
+                write!(
+                    f,
+                    "SGI target {aff:#010x} differs from the first target above affinity level 0"
+                )
+            }
+            GicError::NotImplemented(id) => write!(f, "{id:?} is not implemented"),
+            GicError::UnknownAffinity(aff) => {
+                write!(f, "no redistributor with affinity {aff:#010x}")
+            }
+            GicError::AffinityRoutingEnabled => {
+                write!(f, "legacy register is not accessible with affinity routing")
+            }
+            GicError::RedistributorRegionTooSmall => {
+                write!(f, "redistributor region ends before the last redistributor")
+            }
+            GicError::InvalidCpuInterface(cpu) => {
+                write!(f, "CPU interface {cpu} is not implemented")
+            }
+            GicError::SecureGroupUnavailable => {
+                write!(f, "Secure Group 1 requires the Secure state")
+            }
+        }
+    }
+}
+
+impl core::error::Error for GicError {}
+
+/// JEP106 implementer code of Arm in GICD_IIDR/GICC_IIDR.
+pub const IMPLEMENTER_ARM: u16 = 0x43b;
+
+/// Decoded Implementer Identification Register (GICD_IIDR).
+///
+/// The product ID is only unique within an architecture version (GIC-400
+/// and GIC-600 both report `0x02`), so the GIC version is kept alongside the
+/// raw value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Iidr {
+    raw: u32,
+    gic_version: u8,
+}
+
+impl Iidr {
+    /// Decode a raw IIDR value read from a GICv`gic_version` distributor
+    pub const fn new(raw: u32, gic_version: u8) -> Self {
+        Self { raw, gic_version }
+    }
+
+    /// Raw register value
+    pub const fn raw(&self) -> u32 {
+        self.raw
+    }
+
+    /// JEP106 implementer code, e.g. [`IMPLEMENTER_ARM`]
+    pub const fn implementer(&self) -> u16 {
+        (self.raw & 0xfff) as u16
+    }
+
+    /// Revision number, the `p` in `rNpM`
+    pub const fn revision(&self) -> u8 {
+        ((self.raw >> 12) & 0xf) as u8
+    }
+
+    /// Variant number, the `r` in `rNpM`
+    pub const fn variant(&self) -> u8 {
+        ((self.raw >> 16) & 0xf) as u8
+    }
+
+    /// Implementation defined product identifier
+    pub const fn product_id(&self) -> u8 {
+        (self.raw >> 24) as u8
+    }
+
+    /// Human readable implementer name, if known
+    pub const fn implementer_name(&self) -> Option<&'static str> {
+        match self.implementer() {
+            IMPLEMENTER_ARM => Some("Arm"),
+            _ => None,
+        }
+    }
+
+    /// Human readable product name, if known
+    pub const fn product_name(&self) -> Option<&'static str> {
+        if self.implementer() != IMPLEMENTER_ARM {
+            return None;
+        }
+        match (self.gic_version, self.product_id()) {
+            (2, 0x02) => Some("GIC-400"),
+            (3 | 4, 0x00) => Some("GIC-500"),
+            (3 | 4, 0x02) => Some("GIC-600"),
+            (3 | 4, 0x04) => Some("GIC-700"),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Iidr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (self.implementer_name(), self.product_name()) {
+            (Some(implementer), Some(product)) => write!(f, "{implementer} {product}")?,
+            (Some(implementer), None) => {
+                write!(f, "{implementer} product {:#x}", self.product_id())?
+            }
+            (None, _) => write!(
+                f,
+                "implementer {:#x} product {:#x}",
+                self.implementer(),
+                self.product_id()
+            )?,
+        }
+        write!(f, " r{}p{}", self.variant(), self.revision())
+    }
+}
+
+/// Error returned by the checked [`IntId`] constructors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntIdError {
+    /// The SGI number is not in [`IntId::SGI_NUMBERS`].
+    InvalidSgi(u32),
+    /// The PPI number is not in [`IntId::PPI_NUMBERS`].
+    InvalidPpi(u32),
+    /// The SPI number is not in [`IntId::SPI_NUMBERS`].
+    InvalidSpi(u32),
+    /// The Extended SPI number is not in [`IntId::ESPI_NUMBERS`].
+    InvalidEspi(u32),
+    /// The Extended PPI number is not in [`IntId::EPPI_NUMBERS`].
+    InvalidEppi(u32),
+}
+
+impl Display for IntIdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            IntIdError::InvalidSgi(n) => write!(f, "SGI number {n} out of range 0..16"),
+            IntIdError::InvalidPpi(n) => write!(f, "PPI number {n} out of range 0..16"),
+            IntIdError::InvalidSpi(n) => write!(f, "SPI number {n} out of range 0..988"),
+            IntIdError::InvalidEspi(n) => write!(f, "ESPI number {n} out of range 0..1024"),
+            IntIdError::InvalidEppi(n) => write!(f, "EPPI number {n} out of range 0..64"),
+        }
+    }
+}
+
+impl core::error::Error for IntIdError {}
+
 /// Interrupt trigger type configuration.
 ///
 /// Defines whether an interrupt is triggered on signal edges or levels.
@@ -23,6 +284,29 @@ pub enum Trigger {
     Level,
 }
 
+/// A requested trigger mode that the GIC cannot express.
+///
+/// GICD_ICFGR/GICR_ICFGR hold one bit per interrupt, edge or level, so
+/// modes from other interrupt controller models (e.g. the device tree
+/// `IRQ_TYPE_EDGE_BOTH`) are rejected instead of being narrowed to [`Trigger::Edge`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerError {
+    /// Triggering on both edges was requested.
+    BothEdgesUnsupported,
+}
+
+impl Display for TriggerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TriggerError::BothEdgesUnsupported => {
+                write!(f, "the GIC cannot trigger on both edges")
+            }
+        }
+    }
+}
+
+impl core::error::Error for TriggerError {}
+
 /// Configuration for setting up an interrupt.
 ///
 /// Contains all necessary information to configure an interrupt in the GIC,
@@ -80,6 +364,44 @@ pub const SPECIAL_RANGE: Range<u32> = Range {
     end: 1024,
 };
 
+/// Interrupt ID range for Extended PPIs (GICv3.1).
+/// Range: 1056-1119 (64 interrupts total)
+pub const EPPI_RANGE: Range<u32> = Range {
+    start: 1056,
+    end: 1120,
+};
+
+/// Interrupt ID range for Extended SPIs (GICv3.1).
+/// Range: 4096-5119 (1024 interrupts total)
+pub const ESPI_RANGE: Range<u32> = Range {
+    start: 4096,
+    end: 5120,
+};
+
+/// First interrupt ID of Locality-specific Peripheral Interrupts (LPIs).
+pub const LPI_START: u32 = 8192;
+
+/// Class of an interrupt ID, see [`IntId::classify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntClass {
+    /// Software Generated Interrupt (0-15)
+    Sgi,
+    /// Private Peripheral Interrupt (16-31)
+    Ppi,
+    /// Shared Peripheral Interrupt (32-1019)
+    Spi,
+    /// Special interrupt ID (1020-1023), e.g. spurious
+    Special,
+    /// Extended PPI (1056-1119)
+    Eppi,
+    /// Extended SPI (4096-5119)
+    Espi,
+    /// Locality-specific Peripheral Interrupt (8192 and above)
+    Lpi,
+    /// Reserved interrupt ID
+    Reserved,
+}
+
 /// An interrupt identifier (INTID) for the GIC.
 ///
 /// Represents a unique interrupt ID that can be used with the GIC hardware.
@@ -109,6 +431,36 @@ pub const SPECIAL_RANGE: Range<u32> = Range {
 pub struct IntId(u32);
 
 impl IntId {
+    /// Accepted SGI numbers for [`IntId::sgi`] and [`IntId::try_sgi`].
+    pub const SGI_NUMBERS: Range<u32> = Range {
+        start: 0,
+        end: SGI_RANGE.end - SGI_RANGE.start,
+    };
+
+    /// Accepted PPI numbers for [`IntId::ppi`] and [`IntId::try_ppi`].
+    pub const PPI_NUMBERS: Range<u32> = Range {
+        start: 0,
+        end: PPI_RANGE.end - PPI_RANGE.start,
+    };
+
+    /// Accepted SPI numbers for [`IntId::spi`] and [`IntId::try_spi`].
+    pub const SPI_NUMBERS: Range<u32> = Range {
+        start: 0,
+        end: SPI_RANGE.end - SPI_RANGE.start,
+    };
+
+    /// Accepted Extended SPI numbers for [`IntId::espi`] and [`IntId::try_espi`].
+    pub const ESPI_NUMBERS: Range<u32> = Range {
+        start: 0,
+        end: ESPI_RANGE.end - ESPI_RANGE.start,
+    };
+
+    /// Accepted Extended PPI numbers for [`IntId::eppi`] and [`IntId::try_eppi`].
+    pub const EPPI_NUMBERS: Range<u32> = Range {
+        start: 0,
+        end: EPPI_RANGE.end - EPPI_RANGE.start,
+    };
+
     /// Create a new `IntId` from a raw interrupt ID.
     ///
     /// # Arguments
@@ -156,8 +508,32 @@ impl IntId {
     /// assert!(sgi1.is_sgi());
     /// ```
     pub const fn sgi(sgi: u32) -> Self {
-        assert!(sgi < SGI_RANGE.end);
-        Self(sgi)
+        match Self::try_sgi(sgi) {
+            Ok(id) => id,
+            Err(_) => panic!("SGI number out of range, must be less than 16"),
+        }
+    }
+
+    /// Create an interrupt ID for a Software Generated Interrupt, checking the range.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IntIdError::InvalidSgi`] if `sgi` is not in [`IntId::SGI_NUMBERS`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use arm_gic_driver::IntId;
+    ///
+    /// assert!(IntId::try_sgi(15).is_ok());
+    /// assert!(IntId::try_sgi(16).is_err());
+    /// ```
+    pub const fn try_sgi(sgi: u32) -> Result<Self, IntIdError> {
+        if sgi < Self::SGI_NUMBERS.end {
+            Ok(Self(SGI_RANGE.start + sgi))
+        } else {
+            Err(IntIdError::InvalidSgi(sgi))
+        }
     }
 
     /// Create an interrupt ID for a Private Peripheral Interrupt.
@@ -183,8 +559,32 @@ impl IntId {
     /// assert!(ppi2.is_private());
     /// ```
     pub const fn ppi(ppi: u32) -> Self {
-        assert!(ppi < PPI_RANGE.end - PPI_RANGE.start);
-        Self(PPI_RANGE.start + ppi)
+        match Self::try_ppi(ppi) {
+            Ok(id) => id,
+            Err(_) => panic!("PPI number out of range, must be less than 16"),
+        }
+    }
+
+    /// Create an interrupt ID for a Private Peripheral Interrupt, checking the range.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IntIdError::InvalidPpi`] if `ppi` is not in [`IntId::PPI_NUMBERS`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use arm_gic_driver::IntId;
+    ///
+    /// assert_eq!(IntId::try_ppi(14).unwrap().to_u32(), 30);
+    /// assert!(IntId::try_ppi(40).is_err());
+    /// ```
+    pub const fn try_ppi(ppi: u32) -> Result<Self, IntIdError> {
+        if ppi < Self::PPI_NUMBERS.end {
+            Ok(Self(PPI_RANGE.start + ppi))
+        } else {
+            Err(IntIdError::InvalidPpi(ppi))
+        }
     }
 
     /// Create an interrupt ID for a Shared Peripheral Interrupt.
@@ -198,7 +598,7 @@ impl IntId {
     ///
     /// # Panics
     ///
-    /// Panics if `spi` would result in an interrupt ID >= 1020.
+    /// Panics if `spi` is greater than or equal to 988 (interrupt ID >= 1020).
     ///
     /// # Examples
     ///
@@ -210,8 +610,106 @@ impl IntId {
     /// assert!(!spi42.is_private());
     /// ```
     pub const fn spi(spi: u32) -> Self {
-        assert!(spi < SPECIAL_RANGE.start);
-        Self(SPI_RANGE.start + spi)
+        match Self::try_spi(spi) {
+            Ok(id) => id,
+            Err(_) => panic!("SPI number out of range, must be less than 988"),
+        }
+    }
+
+    /// Create an interrupt ID for a Shared Peripheral Interrupt, checking the range.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IntIdError::InvalidSpi`] if `spi` is not in [`IntId::SPI_NUMBERS`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use arm_gic_driver::IntId;
+    ///
+    /// assert_eq!(IntId::try_spi(987).unwrap().to_u32(), 1019);
+    /// assert!(IntId::try_spi(988).is_err());
+    /// ```
+    pub const fn try_spi(spi: u32) -> Result<Self, IntIdError> {
+        if spi < Self::SPI_NUMBERS.end {
+            Ok(Self(SPI_RANGE.start + spi))
+        } else {
+            Err(IntIdError::InvalidSpi(spi))
+        }
+    }
+
+    /// Create an interrupt ID for an Extended SPI (GICv3.1).
+    ///
+    /// The actual interrupt ID will be `espi + 4096`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `espi` is not in [`IntId::ESPI_NUMBERS`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use arm_gic_driver::IntId;
+    ///
+    /// let espi = IntId::espi(5);
+    /// assert_eq!(espi.to_u32(), 4101);
+    /// assert!(espi.is_espi());
+    /// ```
+    pub const fn espi(espi: u32) -> Self {
+        match Self::try_espi(espi) {
+            Ok(id) => id,
+            Err(_) => panic!("ESPI number out of range, must be less than 1024"),
+        }
+    }
+
+    /// Create an interrupt ID for an Extended SPI, checking the range.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IntIdError::InvalidEspi`] if `espi` is not in [`IntId::ESPI_NUMBERS`].
+    pub const fn try_espi(espi: u32) -> Result<Self, IntIdError> {
+        if espi < Self::ESPI_NUMBERS.end {
+            Ok(Self(ESPI_RANGE.start + espi))
+        } else {
+            Err(IntIdError::InvalidEspi(espi))
+        }
+    }
+
+    /// Create an interrupt ID for an Extended PPI (GICv3.1).
+    ///
+    /// The actual interrupt ID will be `eppi + 1056`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `eppi` is not in [`IntId::EPPI_NUMBERS`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use arm_gic_driver::IntId;
+    ///
+    /// let eppi = IntId::eppi(2);
+    /// assert_eq!(eppi.to_u32(), 1058);
+    /// assert!(eppi.is_eppi());
+    /// ```
+    pub const fn eppi(eppi: u32) -> Self {
+        match Self::try_eppi(eppi) {
+            Ok(id) => id,
+            Err(_) => panic!("EPPI number out of range, must be less than 64"),
+        }
+    }
+
+    /// Create an interrupt ID for an Extended PPI, checking the range.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IntIdError::InvalidEppi`] if `eppi` is not in [`IntId::EPPI_NUMBERS`].
+    pub const fn try_eppi(eppi: u32) -> Result<Self, IntIdError> {
+        if eppi < Self::EPPI_NUMBERS.end {
+            Ok(Self(EPPI_RANGE.start + eppi))
+        } else {
+            Err(IntIdError::InvalidEppi(eppi))
+        }
     }
 
     /// Check if this interrupt ID is for a Software Generated Interrupt.
@@ -254,6 +752,28 @@ impl IntId {
         self.0 < SPI_RANGE.start
     }
 
+    /// Check if this interrupt's state is banked per CPU in the redistributor.
+    ///
+    /// On GICv3, the configuration of SGIs, PPIs and Extended PPIs lives in
+    /// the SGI frame of each redistributor, while SPIs and Extended SPIs are
+    /// configured through the distributor. Accesses for a banked interrupt
+    /// only affect the current CPU.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use arm_gic_driver::IntId;
+    ///
+    /// assert!(IntId::sgi(3).is_redistributor_banked());
+    /// assert!(IntId::ppi(14).is_redistributor_banked());
+    /// assert!(IntId::eppi(2).is_redistributor_banked());
+    /// assert!(!IntId::spi(0).is_redistributor_banked());
+    /// assert!(!IntId::espi(0).is_redistributor_banked());
+    /// ```
+    pub fn is_redistributor_banked(&self) -> bool {
+        self.is_private() || self.is_eppi()
+    }
+
     /// Get the raw interrupt ID as a u32 value.
     ///
     /// # Returns
@@ -295,16 +815,129 @@ impl IntId {
     pub fn is_special(&self) -> bool {
         SPECIAL_RANGE.contains(&self.0)
     }
+
+    /// Check if this interrupt ID is an Extended SPI (4096-5119).
+    pub fn is_espi(&self) -> bool {
+        ESPI_RANGE.contains(&self.0)
+    }
+
+    /// Check if this interrupt ID is an Extended PPI (1056-1119).
+    pub fn is_eppi(&self) -> bool {
+        EPPI_RANGE.contains(&self.0)
+    }
+
+    /// Number of this interrupt within its [`IntClass`].
+    ///
+    /// This is the argument that recreates the ID through the matching
+    /// constructor, e.g. `n` for [`IntId::espi`]`(n)`, and is suitable as an
+    /// index into a per-class handler table. LPI, special and reserved IDs
+    /// return the raw INTID.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use arm_gic_driver::{IntClass, IntId};
+    ///
+    /// let id = unsafe { IntId::raw(4096 + 7) };
+    /// assert_eq!(id.classify(), IntClass::Espi);
+    /// assert_eq!(id.logical_number(), 7);
+    /// assert_eq!(IntId::spi(10).logical_number(), 10);
+    /// ```
+    pub const fn logical_number(&self) -> u32 {
+        match self.classify() {
+            IntClass::Sgi => self.0 - SGI_RANGE.start,
+            IntClass::Ppi => self.0 - PPI_RANGE.start,
+            IntClass::Spi => self.0 - SPI_RANGE.start,
+            IntClass::Eppi => self.0 - EPPI_RANGE.start,
+            IntClass::Espi => self.0 - ESPI_RANGE.start,
+            IntClass::Special | IntClass::Lpi | IntClass::Reserved => self.0,
+        }
+    }
+
+    /// Classify this interrupt ID by range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use arm_gic_driver::{IntClass, IntId};
+    ///
+    /// assert_eq!(IntId::ppi(3).classify(), IntClass::Ppi);
+    /// assert_eq!(unsafe { IntId::raw(1023) }.classify(), IntClass::Special);
+    /// assert_eq!(unsafe { IntId::raw(8192) }.classify(), IntClass::Lpi);
+    /// ```
+    pub const fn classify(&self) -> IntClass {
+        match self.0 {
+            0..16 => IntClass::Sgi,
+            16..32 => IntClass::Ppi,
+            32..1020 => IntClass::Spi,
+            1020..1024 => IntClass::Special,
+            1056..1120 => IntClass::Eppi,
+            4096..5120 => IntClass::Espi,
+            LPI_START.. => IntClass::Lpi,
+            _ => IntClass::Reserved,
+        }
+    }
+
+    /// Move this interrupt ID by `n` within its [`IntClass`].
+    ///
+    /// Returns `None` if the result leaves the class, overflows, or if this
+    /// is a special or reserved ID.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use arm_gic_driver::IntId;
+    ///
+    /// assert_eq!(IntId::spi(10).offset(5), Some(IntId::spi(15)));
+    /// assert_eq!(IntId::spi(0).offset(-1), None); // would be PPI 15
+    /// assert_eq!(IntId::sgi(15).offset(1), None);
+    /// ```
+    pub fn offset(self, n: i32) -> Option<IntId> {
+        let class = self.classify();
+        if matches!(class, IntClass::Special | IntClass::Reserved) {
+            return None;
+        }
+        let id = IntId(self.0.checked_add_signed(n)?);
+        (id.classify() == class).then_some(id)
+    }
+
+    /// Iterate over the interrupt IDs from `start` up to, but not including, `end`.
+    ///
+    /// The iteration stops early at the end of the [`IntClass`] of `start`,
+    /// so it never yields IDs of another class. Special and reserved `start`
+    /// IDs yield nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use arm_gic_driver::IntId;
+    ///
+    /// let ids: Vec<_> = IntId::range(IntId::spi(4), IntId::spi(7)).collect();
+    /// assert_eq!(ids, [IntId::spi(4), IntId::spi(5), IntId::spi(6)]);
+    ///
+    /// // Stops at the last PPI
+    /// assert_eq!(IntId::range(IntId::ppi(14), IntId::spi(2)).count(), 2);
+    /// ```
+    pub fn range(start: IntId, end: IntId) -> impl Iterator<Item = IntId> {
+        core::iter::successors(Some(start).filter(|id| id.offset(0).is_some()), |id| {
+            id.offset(1)
+        })
+        .take_while(move |id| *id < end)
+    }
 }
 
 impl Debug for IntId {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        match self.0 {
-            0..16 => write!(f, "SGI {}", self.0 - SGI_RANGE.start),
-            16..32 => write!(f, "PPI {}", self.0 - PPI_RANGE.start),
-            32..1020 => write!(f, "SPI {}", self.0 - SPI_RANGE.start),
-            1020..1024 => write!(f, "Special IntId{}", self.0),
-            _ => write!(f, "Invalid IntId{}", self.0),
+        let n = self.logical_number();
+        match self.classify() {
+            IntClass::Sgi => write!(f, "SGI {n}"),
+            IntClass::Ppi => write!(f, "PPI {n}"),
+            IntClass::Spi => write!(f, "SPI {n}"),
+            IntClass::Special => write!(f, "Special IntId{}", self.0),
+            IntClass::Eppi => write!(f, "EPPI {n}"),
+            IntClass::Espi => write!(f, "ESPI {n}"),
+            IntClass::Lpi => write!(f, "LPI {}", self.0),
+            IntClass::Reserved => write!(f, "Invalid IntId{}", self.0),
         }
     }
 }
@@ -314,3 +947,177 @@ impl From<IntId> for u32 {
         intid.0
     }
 }
+
+/// Fixed-size table translating guest-visible (virtual) interrupt IDs to host
+/// physical interrupt IDs.
+///
+/// Physical IDs must be SPIs, and each virtual and physical ID can appear in
+/// at most one entry.
+///
+/// # Examples
+///
+/// ```
+/// use arm_gic_driver::{IntId, IntIdRemap};
+///
+/// let mut remap = IntIdRemap::<8>::new();
+/// remap.map(IntId::spi(1), IntId::spi(40)).unwrap();
+/// assert_eq!(remap.to_physical(IntId::spi(1)), Some(IntId::spi(40)));
+/// assert_eq!(remap.to_virtual(IntId::spi(40)), Some(IntId::spi(1)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct IntIdRemap<const N: usize> {
+    entries: [Option<(IntId, IntId)>; N],
+}
+
+impl<const N: usize> IntIdRemap<N> {
+    /// Create an empty table.
+    pub const fn new() -> Self {
+        Self { entries: [None; N] }
+    }
+
+    /// Map the guest interrupt `virt` to the physical SPI `phys`.
+    ///
+    /// # Errors
+    ///
+    /// - [`GicError::NotSpi`] if `phys` is not an SPI.
+    /// - [`GicError::AlreadyMapped`] if `virt` or `phys` already has an entry.
+    /// - [`GicError::RemapTableFull`] if all `N` entries are in use.
+    pub fn map(&mut self, virt: IntId, phys: IntId) -> Result<(), GicError> {
+        if !SPI_RANGE.contains(&phys.to_u32()) {
+            return Err(GicError::NotSpi(phys));
+        }
+        if self.to_physical(virt).is_some() {
+            return Err(GicError::AlreadyMapped(virt));
+        }
+        if self.to_virtual(phys).is_some() {
+            return Err(GicError::AlreadyMapped(phys));
+        }
+        let slot = self
+            .entries
+            .iter_mut()
+            .find(|e| e.is_none())
+            .ok_or(GicError::RemapTableFull)?;
+        *slot = Some((virt, phys));
+        Ok(())
+    }
+
+    /// Remove the entry for the guest interrupt `virt`, returning its physical ID.
+    pub fn unmap(&mut self, virt: IntId) -> Option<IntId> {
+        let slot = self
+            .entries
+            .iter_mut()
+            .find(|e| matches!(e, Some((v, _)) if *v == virt))?;
+        slot.take().map(|(_, p)| p)
+    }
+
+    /// Translate a guest interrupt ID to the host physical ID.
+    pub fn to_physical(&self, virt: IntId) -> Option<IntId> {
+        self.entries
+            .iter()
+            .flatten()
+            .find(|(v, _)| *v == virt)
+            .map(|(_, p)| *p)
+    }
+
+    /// Translate a host physical ID to the guest interrupt ID.
+    pub fn to_virtual(&self, phys: IntId) -> Option<IntId> {
+        self.entries
+            .iter()
+            .flatten()
+            .find(|(_, p)| *p == phys)
+            .map(|(v, _)| *v)
+    }
+
+    /// Iterate over all `(virtual, physical)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (IntId, IntId)> + '_ {
+        self.entries.iter().flatten().copied()
+    }
+}
+
+impl<const N: usize> Default for IntIdRemap<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A CPU in a form that converts to the interrupt target encoding of either
+/// GIC version.
+///
+/// GICv3 targets a CPU by its MPIDR affinity. GICv2 targets a CPU interface
+/// number, a bit in GICD_ITARGETSR or GICD_SGIR, which the SoC assigns and
+/// which cannot be derived from MPIDR in general. [`CpuTarget::from_mpidr`]
+/// uses Aff0 as the interface number when Aff1-Aff3 are zero, which matches
+/// single-cluster systems; override it with [`CpuTarget::with_v2_interface`],
+/// e.g. with [`crate::v2::Gic::current_cpu_interface`] on the CPU itself.
+///
+/// # Examples
+///
+/// ```
+/// use arm_gic_driver::CpuTarget;
+///
+/// let cpu = CpuTarget::from_mpidr(0x8000_0002);
+/// assert_eq!(cpu.to_v2_mask_bit(), Some(0b100));
+///
+/// // Second cluster of a big.LITTLE system, interfaces 4-7
+/// let cpu = CpuTarget::from_mpidr(0x8000_0101);
+/// assert_eq!(cpu.to_v2_mask_bit(), None);
+/// assert_eq!(cpu.with_v2_interface(5).to_v2_mask_bit(), Some(0b10_0000));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CpuTarget {
+    /// `Aff3.Aff2.Aff1.Aff0`
+    affinity: u32,
+    v2_interface: Option<u8>,
+}
+
+impl CpuTarget {
+    /// Create a target from an MPIDR_EL1 value.
+    pub const fn from_mpidr(mpidr: u64) -> Self {
+        let affinity = (mpidr & 0xFF_FFFF) as u32 | (((mpidr >> 32) & 0xFF) as u32) << 24;
+        let v2_interface = if affinity < 8 {
+            Some(affinity as u8)
+        } else {
+            None
+        };
+        Self {
+            affinity,
+            v2_interface,
+        }
+    }
+
+    /// Use `interface` as the GICv2 CPU interface number of this CPU.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interface` is not below 8.
+    pub const fn with_v2_interface(self, interface: u8) -> Self {
+        assert!(interface < 8, "Invalid CPU Interface");
+        Self {
+            v2_interface: Some(interface),
+            ..self
+        }
+    }
+
+    /// GICv2 CPU interface number, if known.
+    pub const fn v2_interface(&self) -> Option<usize> {
+        match self.v2_interface {
+            Some(interface) => Some(interface as usize),
+            None => None,
+        }
+    }
+
+    /// GICD_ITARGETSR / GICD_SGIR target mask bit of this CPU, if the
+    /// GICv2 CPU interface number is known.
+    pub const fn to_v2_mask_bit(&self) -> Option<u8> {
+        match self.v2_interface {
+            Some(interface) => Some(1 << interface),
+            None => None,
+        }
+    }
+
+    /// GICv3 affinity of this CPU.
+    #[cfg(any(target_arch = "aarch64", doc))]
+    pub fn to_v3_affinity(&self) -> crate::v3::Affinity {
+        crate::v3::Affinity::from_affinity(self.affinity)
+    }
+}
@@ -31,7 +31,10 @@ use core::{
     ptr::NonNull,
 };
 
-pub use define::IntId;
+pub use define::{
+    CpuTarget, GicError, IMPLEMENTER_ARM, Iidr, IntClass, IntId, IntIdError, IntIdRemap,
+    TriggerError,
+};
 pub use version::*;
 
 /// Virtual address wrapper for memory-mapped register access.
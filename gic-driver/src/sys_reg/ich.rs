@@ -153,6 +153,8 @@ tock_registers::register_bitfields! {
         NMI OFFSET(59) NUMBITS(1) [],
         PRIORITY OFFSET(48) NUMBITS(8) [],
         PINTID OFFSET(32) NUMBITS(16) [],
+        /// EOI maintenance interrupt request, shares bit 41 with PINTID when HW is clear
+        EOI OFFSET(41) NUMBITS(1) [],
     ]
 }
 
@@ -257,6 +259,14 @@ macro_rules! ich_lr_el2_case {
     };
 }
 
+/// Number of implemented list registers (ICH_VTR_EL2.ListRegs + 1).
+///
+/// Accessing an `ICH_LR<n>_EL2` at or above this count is UNDEFINED, so list
+/// register scans must stop here rather than at the architectural maximum of 16.
+pub fn ich_lr_count() -> usize {
+    ICH_VTR_EL2.read(ICH_VTR_EL2::LISTREGS) as usize + 1
+}
+
 pub fn ich_lr_el2_get(n: usize) -> LocalRegisterCopy<u64, ICH_LR_EL2::Register> {
     ich_lr_el2_case!(n, extract())
 }
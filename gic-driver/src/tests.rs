@@ -1,26 +1,29 @@
 extern crate std;
-use crate::{
-    IntId,
-    version::v3::{LPI, RedistributorV3, RedistributorV4, SGI},
-};
+#[cfg(target_arch = "aarch64")]
+use crate::version::v3::{LPI, RedistributorV3, RedistributorV4, SGI};
+use crate::{IntId, IntIdError};
 
+#[cfg(target_arch = "aarch64")]
 #[test]
 fn size_lpi() {
     let size = size_of::<LPI>();
     assert_eq!(size, 0x10000);
 }
 
+#[cfg(target_arch = "aarch64")]
 #[test]
 fn size_sgi() {
     assert_eq!(size_of::<SGI>(), 0x10000);
 }
 
+#[cfg(target_arch = "aarch64")]
 #[test]
 fn test_v3_rd() {
     let size = size_of::<RedistributorV3>();
     assert_eq!(size, 0x20000);
 }
 
+#[cfg(target_arch = "aarch64")]
 #[test]
 fn test_v4_rd() {
     let size = size_of::<RedistributorV4>();
@@ -40,3 +43,1186 @@ fn test_ppi() {
     let id = IntId::ppi(17);
     assert_eq!(id.is_private(), true);
 }
+
+#[test]
+#[should_panic]
+fn test_spi() {
+    let _ = IntId::spi(988);
+}
+
+#[test]
+fn test_try_intid() {
+    assert_eq!(IntId::try_sgi(15).unwrap().to_u32(), 15);
+    assert_eq!(IntId::try_sgi(16), Err(IntIdError::InvalidSgi(16)));
+    assert_eq!(IntId::try_ppi(15).unwrap().to_u32(), 31);
+    assert_eq!(IntId::try_ppi(40), Err(IntIdError::InvalidPpi(40)));
+    assert_eq!(IntId::try_spi(987).unwrap().to_u32(), 1019);
+    assert_eq!(IntId::try_spi(988), Err(IntIdError::InvalidSpi(988)));
+}
+
+#[test]
+fn test_vmcr_pri_mask() {
+    use crate::version::v2::vmcr_pri_mask;
+
+    assert_eq!(vmcr_pri_mask(0xFF, 5), 0x1F);
+    assert_eq!(vmcr_pri_mask(0xA0, 5), 0x14);
+    assert_eq!(vmcr_pri_mask(0xF8, 4), 0x1E);
+    assert_eq!(vmcr_pri_mask(0xA8, 3), 0x14);
+    assert_eq!(vmcr_pri_mask(0x07, 5), 0);
+}
+
+#[test]
+fn test_guest_priority_mask() {
+    use crate::version::v2::HypervisorInterface;
+
+    let mut regs = [0u32; 0x80];
+    let base = regs.as_mut_ptr();
+    // GICH_VTR: PRIbits = 4 (5 bits), ListRegs = 3
+    unsafe { base.add(1).write_volatile((4 << 29) | 3) };
+    let hv = unsafe { HypervisorInterface::new(base as *mut u8, core::ptr::null_mut()) };
+
+    hv.set_guest_priority_mask(0xF0);
+    assert_eq!(hv.guest_priority_mask(), 0xF0);
+
+    // PRIbits = 2 (3 bits): 0xF0 loses bit 4
+    unsafe { base.add(1).write_volatile((2 << 29) | 3) };
+    hv.set_guest_priority_mask(0xF0);
+    assert_eq!(hv.guest_priority_mask(), 0xE0);
+}
+
+#[test]
+fn test_list_register_bounds() {
+    use crate::GicError;
+    use crate::version::v2::{
+        HypervisorInterface, VirtualInterruptConfig, VirtualInterruptState, VirtualInterruptType,
+    };
+
+    let mut regs = [0u32; 0x80];
+    let base = regs.as_mut_ptr();
+    // GICH_VTR: ListRegs = 3, four list registers
+    unsafe { base.add(1).write_volatile(3) };
+    let hv = unsafe { HypervisorInterface::new(base as *mut u8, core::ptr::null_mut()) };
+
+    let config = VirtualInterruptConfig::new(
+        IntId::spi(5),
+        0xA0,
+        VirtualInterruptState::Pending,
+        true,
+        VirtualInterruptType::hardware(37),
+    );
+    assert!(hv.set_virtual_interrupt(3, config).is_ok());
+    assert_eq!(
+        hv.get_virtual_interrupt(3).unwrap().virtual_id,
+        IntId::spi(5)
+    );
+    assert_eq!(
+        hv.set_virtual_interrupt(4, config),
+        Err(GicError::InvalidListRegister(4))
+    );
+    assert!(hv.get_virtual_interrupt(10).is_err());
+    // GICH_LR4 is left untouched
+    assert_eq!(unsafe { base.add(0x44).read_volatile() }, 0);
+}
+
+#[test]
+fn test_vmcr_roundtrip() {
+    use crate::version::v2::{HypervisorInterface, VmcrConfig};
+
+    let mut regs = [0u32; 0x80];
+    let base = regs.as_mut_ptr();
+    // GICH_VTR: PRIbits = 4 (5 bits)
+    unsafe { base.add(1).write_volatile(4 << 29) };
+    let hv = unsafe { HypervisorInterface::new(base as *mut u8, core::ptr::null_mut()) };
+
+    let vmcr = VmcrConfig {
+        grp1_enable: true,
+        eoi_mode: true,
+        cbpr: true,
+        bpr: 2,
+        abpr: 3,
+        priority_mask: 0xF8,
+        ..Default::default()
+    };
+    hv.set_vmcr(vmcr);
+    assert_eq!(hv.get_vmcr(), vmcr);
+    // VMPriMask [31:27], VMBP [23:21], VMABP [20:18], VEM [9], VMCBPR [4], VMGrp1En [1]
+    let raw = unsafe { base.add(2).read_volatile() };
+    assert_eq!(
+        raw,
+        (0x1F << 27) | (2 << 21) | (3 << 18) | (1 << 9) | (1 << 4) | (1 << 1)
+    );
+}
+
+#[test]
+fn test_would_preempt() {
+    use crate::version::would_preempt;
+
+    // Idle CPU interface: any priority but the lowest preempts
+    assert!(would_preempt(0xA0, 0xFF, 0));
+    assert!(!would_preempt(0xFF, 0xFF, 0));
+    // Same group priority never preempts
+    assert!(!would_preempt(0x81, 0x80, 1));
+    assert!(!would_preempt(0x8F, 0x80, 4));
+    assert!(would_preempt(0x8F, 0x90, 4));
+    assert!(would_preempt(0x7F, 0x80, 4));
+}
+
+#[test]
+fn test_irq_range_masks() {
+    use crate::version::irq_range_masks;
+    use std::vec::Vec;
+
+    let masks: Vec<_> = irq_range_masks(32, 64).collect();
+    assert_eq!(masks, [(1, u32::MAX), (2, u32::MAX)]);
+
+    let masks: Vec<_> = irq_range_masks(40, 40).collect();
+    assert_eq!(masks, [(1, 0xFFFF_FF00), (2, 0x0000_FFFF)]);
+
+    let masks: Vec<_> = irq_range_masks(3, 1).collect();
+    assert_eq!(masks, [(0, 1 << 3)]);
+
+    assert_eq!(irq_range_masks(50, 0).count(), 0);
+}
+
+#[test]
+fn test_intid_remap() {
+    use crate::{GicError, IntIdRemap};
+
+    let mut remap = IntIdRemap::<2>::new();
+    remap.map(IntId::spi(0), IntId::spi(10)).unwrap();
+    assert_eq!(
+        remap.map(IntId::ppi(1), IntId::ppi(1)),
+        Err(GicError::NotSpi(IntId::ppi(1)))
+    );
+    assert_eq!(
+        remap.map(IntId::spi(0), IntId::spi(11)),
+        Err(GicError::AlreadyMapped(IntId::spi(0)))
+    );
+    assert_eq!(
+        remap.map(IntId::spi(1), IntId::spi(10)),
+        Err(GicError::AlreadyMapped(IntId::spi(10)))
+    );
+    remap.map(IntId::ppi(2), IntId::spi(11)).unwrap();
+    assert_eq!(
+        remap.map(IntId::spi(2), IntId::spi(12)),
+        Err(GicError::RemapTableFull)
+    );
+
+    assert_eq!(remap.to_physical(IntId::ppi(2)), Some(IntId::spi(11)));
+    assert_eq!(remap.unmap(IntId::spi(0)), Some(IntId::spi(10)));
+    assert_eq!(remap.to_virtual(IntId::spi(10)), None);
+    remap.map(IntId::spi(2), IntId::spi(12)).unwrap();
+}
+
+#[test]
+fn test_fdt_parse_irq_config() {
+    use crate::{define::Trigger, fdt_parse_irq_config};
+
+    let sgi = fdt_parse_irq_config(&[5]).unwrap();
+    assert_eq!(sgi.id, IntId::sgi(5));
+    assert_eq!(sgi.trigger, Trigger::Edge);
+
+    let spi = fdt_parse_irq_config(&[0, 42, 4]).unwrap();
+    assert_eq!(spi.id, IntId::spi(42));
+    assert_eq!(spi.trigger, Trigger::Level);
+
+    let ppi = fdt_parse_irq_config(&[1, 2, 1]).unwrap();
+    assert_eq!(ppi.id, IntId::ppi(2));
+    assert_eq!(ppi.trigger, Trigger::Edge);
+
+    // Malformed specifiers
+    assert!(fdt_parse_irq_config(&[]).is_err());
+    assert!(fdt_parse_irq_config(&[0, 42]).is_err());
+    assert!(fdt_parse_irq_config(&[16]).is_err());
+    assert!(fdt_parse_irq_config(&[9, 0, 4]).is_err());
+    assert!(fdt_parse_irq_config(&[0, 988, 4]).is_err());
+    assert!(fdt_parse_irq_config(&[1, 16, 4]).is_err());
+    assert!(fdt_parse_irq_config(&[0, 1, 0]).is_err());
+    // IRQ_TYPE_EDGE_BOTH cannot be expressed in ICFGR
+    assert!(fdt_parse_irq_config(&[0, 1, 3]).is_err());
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn test_target_list_range_selector() {
+    use crate::v3::{Affinity, TargetList};
+
+    let aff = |aff0| Affinity {
+        aff0,
+        aff1: 1,
+        aff2: 0,
+        aff3: 0,
+    };
+    let mut list = TargetList::new([aff(3), aff(17)]);
+    list.add(aff(255));
+    let got: std::vec::Vec<u8> = list.affinity_list().map(|a| a.aff0).collect();
+    assert_eq!(got, [3, 17, 255]);
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn test_v3_irouter_round_trip() {
+    use crate::v3::{Affinity, LocalRegisterCopy, decode_route, encode_route};
+
+    let aff = Affinity {
+        aff0: 0x12,
+        aff1: 0x34,
+        aff2: 0x56,
+        aff3: 0x78,
+    };
+    // Aff0-2 in bits [23:0], Interrupt_Routing_Mode in bit 31, Aff3 in bits [39:32]
+    assert_eq!(encode_route(Some(aff)).get(), 0x78_0056_3412);
+    assert_eq!(encode_route(None).get(), 1 << 31);
+    assert_eq!(decode_route(encode_route(Some(aff))), Some(aff));
+    assert_eq!(decode_route(encode_route(None)), None);
+    // The affinity fields are ignored in 1-of-N mode
+    assert_eq!(decode_route(LocalRegisterCopy::new(0x78_8056_3412)), None);
+}
+
+#[test]
+fn test_extended_intid() {
+    use crate::IntClass;
+
+    let espi = IntId::espi(5);
+    assert!(espi.is_espi());
+    assert_eq!(espi.classify(), IntClass::Espi);
+    assert_eq!(espi.logical_number(), 5);
+    assert_eq!(IntId::try_espi(1024), Err(IntIdError::InvalidEspi(1024)));
+
+    let eppi = IntId::eppi(63);
+    assert!(eppi.is_eppi());
+    assert!(!eppi.is_espi());
+    assert_eq!(eppi.logical_number(), 63);
+    assert_eq!(IntId::try_eppi(64), Err(IntIdError::InvalidEppi(64)));
+}
+
+#[test]
+fn test_intid_offset_and_range() {
+    assert_eq!(IntId::ppi(3).offset(-3), Some(IntId::ppi(0)));
+    assert_eq!(IntId::ppi(0).offset(-1), None);
+    assert_eq!(IntId::spi(987).offset(1), None);
+    assert_eq!(IntId::sgi(0).offset(i32::MIN), None);
+    assert_eq!(unsafe { IntId::raw(u32::MAX) }.offset(1), None);
+    assert_eq!(unsafe { IntId::raw(1023) }.offset(0), None);
+
+    let all_sgis: std::vec::Vec<_> = IntId::range(IntId::sgi(0), IntId::spi(0)).collect();
+    assert_eq!(all_sgis.len(), 16);
+    assert_eq!(all_sgis[15], IntId::sgi(15));
+    assert_eq!(IntId::range(IntId::spi(5), IntId::spi(5)).count(), 0);
+    assert_eq!(IntId::range(IntId::spi(5), IntId::spi(2)).count(), 0);
+    assert_eq!(
+        IntId::range(unsafe { IntId::raw(1020) }, unsafe { IntId::raw(1024) }).count(),
+        0
+    );
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn test_v3_ack_extended_intid() {
+    use crate::version::v3::intid_from_iar;
+
+    // ESPI 5 as returned by ICC_IAR1_EL1, RES0 bits above INTID ignored
+    let id = intid_from_iar((1 << 40) | 4101);
+    assert!(id.is_espi());
+    assert_eq!(id, IntId::espi(5));
+    assert_eq!(id.logical_number(), 5);
+
+    assert_eq!(intid_from_iar(1056), IntId::eppi(0));
+    assert!(intid_from_iar(1023).is_special());
+}
+
+/// Zeroed host memory standing in for an MMIO register frame, so that the
+/// register logic can be exercised without hardware.
+struct MockFrame(std::vec::Vec<u32>);
+
+impl MockFrame {
+    /// GICv2 distributor frame size
+    const GICD_FRAME_SIZE: usize = 0x1000;
+    /// GICv2 CPU interface frame size, GICC_DIR is in the second page
+    const GICC_FRAME_SIZE: usize = 0x2000;
+
+    fn new(size: usize) -> Self {
+        Self(std::vec![0; size / 4])
+    }
+
+    fn addr(&mut self) -> crate::VirtAddr {
+        crate::VirtAddr::new(self.0.as_mut_ptr() as usize)
+    }
+
+    fn read(&self, offset: usize) -> u32 {
+        unsafe { self.0.as_ptr().add(offset / 4).read_volatile() }
+    }
+
+    fn read_u8(&self, offset: usize) -> u8 {
+        unsafe { (self.0.as_ptr() as *const u8).add(offset).read_volatile() }
+    }
+
+    fn write(&mut self, offset: usize, val: u32) {
+        unsafe { self.0.as_mut_ptr().add(offset / 4).write_volatile(val) }
+    }
+}
+
+/// Build a GICv2 `Gic` over mock GICD and GICC frames.
+fn mock_v2() -> (MockFrame, MockFrame, crate::v2::Gic) {
+    let mut gicd = MockFrame::new(MockFrame::GICD_FRAME_SIZE);
+    let mut gicc = MockFrame::new(MockFrame::GICC_FRAME_SIZE);
+    // GICD_TYPER: ITLinesNumber = 3 (128 interrupt IDs)
+    gicd.write(0x004, 3);
+    let gic = unsafe { crate::v2::Gic::new(gicd.addr(), gicc.addr(), None) };
+    (gicd, gicc, gic)
+}
+
+#[test]
+fn test_v2_mock_irq_enable() {
+    let (gicd, _gicc, gic) = mock_v2();
+
+    gic.set_irq_enable(IntId::spi(42), true);
+    // ID 74: GICD_ISENABLER2 bit 10
+    assert_eq!(gicd.read(0x108), 1 << 10);
+    assert_eq!(gicd.read(0x104), 0);
+
+    gic.set_irq_enable(IntId::spi(42), false);
+    // GICD_ICENABLER2 bit 10
+    assert_eq!(gicd.read(0x188), 1 << 10);
+}
+
+#[test]
+fn test_v2_clear_pending_range() {
+    let (gicd, _gicc, gic) = mock_v2();
+
+    // IDs 40..100: partial bank 1, full bank 2, partial bank 3
+    gic.clear_pending_range(IntId::spi(8), 60);
+    assert_eq!(gicd.read(0x280), 0);
+    assert_eq!(gicd.read(0x284), 0xFFFF_FF00);
+    assert_eq!(gicd.read(0x288), u32::MAX);
+    assert_eq!(gicd.read(0x28c), 0xF);
+
+    gic.clear_active_range(IntId::spi(0), 1);
+    assert_eq!(gicd.read(0x384), 1);
+    assert_eq!(gicd.read(0x388), 0);
+}
+
+#[test]
+fn test_v2_mock_priority_and_cfg() {
+    use crate::define::Trigger;
+
+    let (gicd, _gicc, gic) = mock_v2();
+
+    gic.set_priority(IntId::spi(42), 0x80);
+    // GICD_IPRIORITYR is byte-addressed per interrupt
+    assert_eq!(gicd.read_u8(0x400 + 74), 0x80);
+    assert_eq!(gic.get_priority(IntId::spi(42)), 0x80);
+
+    gic.set_cfg(IntId::spi(42), Trigger::Edge);
+    // ID 74: GICD_ICFGR4, Int_config[1] of field 10 is bit 21
+    assert_eq!(gicd.read(0xc10), 1 << 21);
+    assert_eq!(gic.get_cfg(IntId::spi(42)), Trigger::Edge);
+}
+
+#[test]
+fn test_v1_init() {
+    let mut gicd = MockFrame::new(MockFrame::GICD_FRAME_SIZE);
+    let mut gicc = MockFrame::new(MockFrame::GICC_FRAME_SIZE);
+    gicd.write(0x004, 3);
+    // GICD_IGROUPR1 is reserved without Security Extensions
+    gicd.write(0x084, 0xdead_beef);
+    // GICD_ICFGR2: 1-N model for every SPI
+    gicd.write(0xc08, 0x5555_5555);
+
+    let mut gic = unsafe { crate::v2::Gic::new_v1(gicd.addr(), gicc.addr()) };
+    assert!(gic.is_v1());
+    gic.init();
+
+    // Only GICD_CTLR.Enable is written
+    assert_eq!(gicd.read(0x000), 1);
+    assert_eq!(gicd.read(0x084), 0xdead_beef);
+    // Level-sensitive SPIs, 1-N model preserved
+    assert_eq!(gicd.read(0xc08), 0x5555_5555);
+}
+
+#[test]
+fn test_v2_init_with_config() {
+    use crate::v2::{InitConfig, InterruptGroup, Trigger};
+
+    let (gicd, _gicc, mut gic) = mock_v2();
+    gic.init_with_config(InitConfig {
+        default_priority: 0x40,
+        default_group: InterruptGroup::Group1,
+        default_trigger: Trigger::Edge,
+        ..Default::default()
+    });
+
+    // GICD_IGROUPR0..3
+    for reg in 0..4 {
+        assert_eq!(gicd.read(0x080 + reg * 4), u32::MAX);
+    }
+    // SPI 0 priority, SGI priorities untouched
+    assert_eq!(gicd.read_u8(0x400 + 32), 0x40);
+    assert_eq!(gicd.read_u8(0x400), 0);
+    // GICD_ICFGR2: edge for every SPI
+    assert_eq!(gicd.read(0xc08), 0xaaaa_aaaa);
+}
+
+#[test]
+fn test_v2_init_spi_priorities_cover_every_spi() {
+    let (gicd, _gicc, mut gic) = mock_v2();
+    gic.init();
+
+    // GICD_IPRIORITYR is byte-indexed by INTID: SPIs 32..128 are all set
+    for id in 32..128 {
+        assert_eq!(gicd.read_u8(0x400 + id), 0xA0, "INTID {id}");
+    }
+    assert_eq!(gicd.read_u8(0x400 + 128), 0);
+    assert_eq!(gicd.read_u8(0x400 + 31), 0);
+}
+
+#[test]
+fn test_v1_detect_variant() {
+    use crate::v2::Variant;
+
+    let (mut gicd, _gicc, mut gic) = mock_v2();
+    assert_eq!(gic.detect_variant(), Variant::V2);
+    // GICD_PIDR2.ArchRev = 1
+    gicd.write(0xfe8, 0x1b);
+    assert_eq!(gic.detect_variant(), Variant::V1);
+    assert!(!gic.cpu_interface().eoi_mode_ns());
+}
+
+#[test]
+#[should_panic(expected = "EOImodeNS is not implemented on GICv1")]
+fn test_v1_rejects_eoi_mode() {
+    let mut gicd = MockFrame::new(MockFrame::GICD_FRAME_SIZE);
+    let mut gicc = MockFrame::new(MockFrame::GICC_FRAME_SIZE);
+    let gic = unsafe { crate::v2::Gic::new_v1(gicd.addr(), gicc.addr()) };
+    gic.cpu_interface().set_eoi_mode_ns(true);
+}
+
+#[test]
+#[should_panic(expected = "GICC_ABPR is optional on GICv1")]
+fn test_v1_rejects_aliased_binary_point() {
+    let mut gicd = MockFrame::new(MockFrame::GICD_FRAME_SIZE);
+    let mut gicc = MockFrame::new(MockFrame::GICC_FRAME_SIZE);
+    let gic = unsafe { crate::v2::Gic::new_v1(gicd.addr(), gicc.addr()) };
+    gic.cpu_interface().set_aliased_binary_point(3);
+}
+
+#[test]
+fn test_v2_trap_op() {
+    use crate::v2::Ack;
+
+    let (_gicd, mut gicc, gic) = mock_v2();
+    let cpu = gic.cpu_interface();
+    cpu.set_eoi_mode_ns(true);
+    cpu.set_priority_mask(0xF0);
+    // GICC_RPR
+    gicc.write(0x0014, 0x80);
+
+    let trap = cpu.trap_operations();
+    let copy = trap;
+    assert!(copy.eoi_mode());
+    assert_eq!(trap.priority_mask(), 0xF0);
+    assert_eq!(trap.running_priority(), 0x80);
+
+    let ack = Ack::SGI {
+        intid: IntId::sgi(3),
+        cpu_id: 2,
+    };
+    trap.eoi(ack);
+    copy.dir(ack);
+    // GICC_EOIR and GICC_DIR: CPUID in bits [12:10]
+    assert_eq!(gicc.read(0x0010), (2 << 10) | 3);
+    assert_eq!(gicc.read(0x1000), (2 << 10) | 3);
+}
+
+#[test]
+fn test_v2_dispatch_stops_at_spurious() {
+    let (_gicd, mut gicc, gic) = mock_v2();
+    let trap = gic.cpu_interface().trap_operations();
+    // GICC_IAR reads 1023 when nothing is pending
+    gicc.write(0x000c, 1023);
+
+    let mut calls = 0;
+    assert_eq!(
+        trap.dispatch(|_| {
+            calls += 1;
+            true
+        }),
+        0
+    );
+    assert_eq!(calls, 0);
+    // Spurious IDs are never completed
+    assert_eq!(gicc.read(0x0010), 0);
+}
+
+#[test]
+fn test_v2_scrub_pending() {
+    let (mut gicd, _gicc, gic) = mock_v2();
+    let id = IntId::spi(5); // INTID 37, bank 1 bit 5
+    // GICD_ISENABLER1: enabled
+    gicd.write(0x104, 1 << 5);
+
+    gic.scrub_pending(id);
+    // GICD_ICENABLER1, GICD_ICPENDR1 written, then re-enabled
+    assert_eq!(gicd.read(0x184), 1 << 5);
+    assert_eq!(gicd.read(0x284), 1 << 5);
+    assert_eq!(gicd.read(0x104), 1 << 5);
+
+    // A disabled interrupt is not enabled by the scrub
+    let (gicd, _gicc, gic) = mock_v2();
+    gic.scrub_pending(id);
+    assert_eq!(gicd.read(0x184), 0);
+    assert_eq!(gicd.read(0x104), 0);
+    assert_eq!(gicd.read(0x284), 1 << 5);
+}
+
+#[test]
+fn test_v2_lockable_spis() {
+    let (mut gicd, _gicc, gic) = mock_v2();
+    // LSPI is only valid with the Security Extensions
+    gicd.write(0x004, (7 << 11) | 3);
+    assert_eq!(gic.lockable_spi_count(), 0);
+
+    // GICD_TYPER: LSPI = 7, SecurityExtn
+    gicd.write(0x004, (7 << 11) | (1 << 10) | 3);
+    assert_eq!(gic.lockable_spi_count(), 7);
+    assert!(gic.is_lockable_spi(IntId::spi(6)));
+    assert!(!gic.is_lockable_spi(IntId::spi(7)));
+    assert!(!gic.is_lockable_spi(IntId::ppi(6)));
+}
+
+#[test]
+fn test_v2_priority_bounds() {
+    use crate::GicError;
+
+    // ITLinesNumber = 3: IDs 0-127 implemented
+    let (gicd, _gicc, gic) = mock_v2();
+    assert_eq!(gic.priority_slice().len(), 128);
+
+    assert!(gic.try_set_priority(IntId::spi(95), 0x40).is_ok());
+    assert_eq!(gicd.read_u8(0x400 + 127), 0x40);
+    assert_eq!(gic.try_get_priority(IntId::spi(95)), Ok(0x40));
+    assert_eq!(
+        gic.try_set_priority(IntId::spi(96), 0x40),
+        Err(GicError::NotImplemented(IntId::spi(96)))
+    );
+    assert!(gic.try_get_priority(IntId::spi(500)).is_err());
+}
+
+#[test]
+#[should_panic]
+fn test_v2_set_priority_unimplemented() {
+    let (_gicd, _gicc, gic) = mock_v2();
+    gic.set_priority(IntId::spi(96), 0x40);
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn test_v3_redistributor_index_for_mpidr() {
+    let mut gicd = MockFrame::new(crate::v3::GICD_FRAME_SIZE);
+    let mut gicr = MockFrame::new(2 * 0x20000);
+    // GICR_TYPER.Affinity in bits [63:32]: 0.0.1.0, then 1.0.1.0 (Last)
+    gicr.write(0x0000c, 0x0000_0100);
+    gicr.write(0x20008, 1 << 4);
+    gicr.write(0x2000c, 0x0100_0100);
+    let gic = unsafe { crate::v3::Gic::new(gicd.addr(), gicr.addr()) };
+
+    assert_eq!(gic.redistributor_index_for_mpidr(0x8000_0100), Some(0));
+    // Aff3 lives in MPIDR bits [39:32] and must not be dropped
+    assert_eq!(gic.redistributor_index_for_mpidr(0x01_8000_0100), Some(1));
+    assert_eq!(gic.redistributor_index_for_mpidr(0x8000_0101), None);
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn test_v3_multi_cluster_mpidr() {
+    use crate::v3::Affinity;
+
+    // MPIDR_EL1 of CPU 3 in cluster 2 of a big.LITTLE SoC: Aff1 = 2, Aff0 = 3
+    let aff = Affinity::from_mpidr(0x8000_0203);
+    assert_eq!(aff.affinity(), 0x0000_0203);
+    // Aff2 in bits [23:16], Aff3 in bits [39:32] of the MPIDR
+    assert_eq!(Affinity::from_mpidr(0x12_8034_0203).affinity(), 0x1234_0203);
+
+    let mut gicd = MockFrame::new(crate::v3::GICD_FRAME_SIZE);
+    let mut gicr = MockFrame::new(3 * 0x20000);
+    // Redistributors of 0.0.0.0, 0.0.1.3 and 0.0.2.3
+    gicr.write(0x2000c, 0x0000_0103);
+    gicr.write(0x40008, 1 << 4);
+    gicr.write(0x4000c, 0x0000_0203);
+    let gic = unsafe { crate::v3::Gic::new(gicd.addr(), gicr.addr()) };
+    assert_eq!(gic.redistributor_index_for_mpidr(0x8000_0203), Some(2));
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn test_v3_set_interrupt_group() {
+    use crate::v3::InterruptGroup;
+
+    let mut gicd = MockFrame::new(crate::v3::GICD_FRAME_SIZE);
+    let mut gicr = MockFrame::new(0x20000);
+    let gic = unsafe { crate::v3::Gic::new(gicd.addr(), gicr.addr()) };
+    let id = IntId::spi(10);
+
+    for group in [
+        InterruptGroup::Group1Secure,
+        InterruptGroup::Group1NonSecure,
+        InterruptGroup::Group0,
+    ] {
+        gic.set_interrupt_group(id, group);
+        let (igroupr, igrpmodr) = group.bits();
+        // INTID 42 is bit 10 of GICD_IGROUPR1 / GICD_IGRPMODR1
+        assert_eq!(gicd.read(0x084), (igroupr as u32) << 10);
+        assert_eq!(gicd.read(0xd04), (igrpmodr as u32) << 10);
+        assert_eq!(gic.get_interrupt_group(id), group);
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn test_v3_eppi_in_redistributor() {
+    use crate::v3::{InterruptGroup, Trigger};
+
+    const SGI_BASE: usize = 0x10000;
+    let mut gicd = MockFrame::new(crate::v3::GICD_FRAME_SIZE);
+    let mut gicr = MockFrame::new(0x20000);
+    // GICR_TYPER: Last, PPInum = 2 (64 Extended PPIs)
+    gicr.write(0x0008, (1 << 4) | (2 << 27));
+    // GICR_TYPER.Affinity: the redistributor of the CPU running the test
+    gicr.write(0x000c, crate::v3::Affinity::current().affinity());
+    let mut gic = unsafe { crate::v3::Gic::new(gicd.addr(), gicr.addr()) };
+
+    let id = IntId::eppi(33);
+    gic.set_irq_enable(id, true);
+    // GICR_ISENABLER2E bit 1
+    assert_eq!(gicr.read(SGI_BASE + 0x108), 1 << 1);
+    assert!(gic.is_irq_enable(id));
+
+    gic.set_priority(id, 0x40);
+    assert_eq!(gicr.read_u8(SGI_BASE + 0x420 + 33), 0x40);
+    assert_eq!(gic.get_priority(id), 0x40);
+
+    gic.set_cfg(id, Trigger::Edge);
+    // GICR_ICFGR4E covers Extended PPIs 32-47, bit 3 is the edge bit of 33
+    assert_eq!(gicr.read(SGI_BASE + 0xc10), 1 << 3);
+    assert_eq!(gic.get_cfg(id), Trigger::Edge);
+
+    gic.set_pending(id, true);
+    assert_eq!(gicr.read(SGI_BASE + 0x208), 1 << 1);
+    gic.set_active(id, true);
+    assert_eq!(gicr.read(SGI_BASE + 0x308), 1 << 1);
+
+    gic.set_interrupt_group(id, InterruptGroup::Group1Secure);
+    assert_eq!(gicr.read(SGI_BASE + 0x088), 0);
+    assert_eq!(gicr.read(SGI_BASE + 0xd08), 1 << 1);
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn test_v3_set_priorities_bounds() {
+    let mut gicd = MockFrame::new(crate::v3::GICD_FRAME_SIZE);
+    let mut gicr = MockFrame::new(0x20000);
+    // GICD_TYPER: ITLinesNumber = 1, INTIDs up to 63
+    gicd.write(0x0004, 1);
+    // GICR_TYPER: Last, PPInum = 1 (32 Extended PPIs)
+    gicr.write(0x0008, (1 << 4) | (1 << 27));
+    // GICR_TYPER.Affinity: the redistributor of the CPU running the test
+    gicr.write(0x000c, crate::v3::Affinity::current().affinity());
+    let gic = unsafe { crate::v3::Gic::new(gicd.addr(), gicr.addr()) };
+
+    let seen = core::cell::Cell::new(0);
+    gic.set_priorities(60..1060, |_| {
+        seen.set(seen.get() + 1);
+        Some(0x40)
+    });
+    // SPIs 60..64 and Extended PPIs 1056..1060
+    assert_eq!(seen.get(), 8);
+    assert_eq!(gicd.read(0x400 + 60), 0x4040_4040);
+    assert_eq!(gicd.read(0x400 + 64), 0);
+    assert_eq!(gicr.read(0x10000 + 0x420), 0x4040_4040);
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn test_v3_init_errors() {
+    use crate::GicError;
+    use crate::v3::{GicBuilder, InterruptGroup, SecurityState};
+
+    let mut gicd = MockFrame::new(crate::v3::GICD_FRAME_SIZE);
+    let mut gicr = MockFrame::new(0x20000);
+    gicr.write(0x0008, 1 << 4);
+
+    let res = unsafe { GicBuilder::new(gicd.addr(), gicr.addr()) }
+        .security_state(SecurityState::NonSecure)
+        .default_group(InterruptGroup::Group1Secure)
+        .build();
+    assert!(matches!(res, Err(GicError::SecureGroupUnavailable)));
+
+    let res = unsafe { GicBuilder::new(gicd.addr(), gicr.addr()) }
+        .security_state(SecurityState::NonSecure)
+        .gicr_size(0x10000)
+        .build();
+    assert!(matches!(res, Err(GicError::RedistributorRegionTooSmall)));
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn test_v3_eligible_cpus_reserved_group() {
+    let mut gicd = MockFrame::new(crate::v3::GICD_FRAME_SIZE);
+    let mut gicr = MockFrame::new(0x20000);
+    gicr.write(0x0008, 1 << 4);
+    let gic = unsafe { crate::v3::Gic::new(gicd.addr(), gicr.addr()) };
+
+    // INTID 42: GICD_IGROUPR1 and GICD_IGRPMODR1 both set (reserved), which
+    // reads as Non-secure Group 1
+    gicd.write(0x084, 1 << 10);
+    gicd.write(0xd04, 1 << 10);
+    // GICD_IROUTER42: 1-of-N
+    gicd.write(0x6000 + 42 * 8, 1 << 31);
+
+    // GICR_CTLR.DPG1S only
+    gicr.write(0x0000, 1 << 26);
+    assert_eq!(gic.eligible_cpus_for(IntId::spi(10)).count(), 1);
+    // GICR_CTLR.DPG1NS
+    gicr.write(0x0000, 1 << 25);
+    assert_eq!(gic.eligible_cpus_for(IntId::spi(10)).count(), 0);
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn test_v3_irq_setup_unimplemented_spi() {
+    use crate::GicError;
+
+    let mut gicd = MockFrame::new(crate::v3::GICD_FRAME_SIZE);
+    let mut gicr = MockFrame::new(0x20000);
+    // GICD_TYPER: ITLinesNumber = 1, INTIDs up to 63
+    gicd.write(0x0004, 1);
+    let mut gic = unsafe { crate::v3::Gic::new(gicd.addr(), gicr.addr()) };
+
+    let id = IntId::spi(40);
+    assert_eq!(
+        gic.configure(id).priority(0x40).enable().apply(),
+        Err(GicError::NotImplemented(id))
+    );
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn test_v3_reinit_keeps_routing() {
+    use crate::v3::{Affinity, Routing};
+
+    let mut gicd = MockFrame::new(crate::v3::GICD_FRAME_SIZE);
+    let mut gicr = MockFrame::new(0x20000);
+    // GICD_TYPER: ITLinesNumber = 1, INTIDs up to 63
+    gicd.write(0x0004, 1);
+    // GICD_CTLR: DS, ARE, EnableGrp1 and EnableGrp0 left on by the old kernel
+    gicd.write(0x0000, (1 << 6) | (1 << 4) | 0b11);
+    gicr.write(0x0008, 1 << 4);
+    let mut gic = unsafe { crate::v3::Gic::new(gicd.addr(), gicr.addr()) };
+
+    gic.reinit().unwrap();
+    assert_eq!(gicd.read(0x0000) & (1 << 4), 1 << 4);
+    let id = IntId::spi(0);
+    assert_eq!(gic.get_routing(id), Routing::Specific(Affinity::current()));
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn test_v3_validate_spi() {
+    use crate::GicError;
+    use crate::v3::{Affinity, Routing, Trigger};
+
+    let mut gicd = MockFrame::new(crate::v3::GICD_FRAME_SIZE);
+    let mut gicr = MockFrame::new(0x20000);
+    // GICD_TYPER: ITLinesNumber = 1, INTIDs up to 63
+    gicd.write(0x0004, 1);
+    let gic = unsafe { crate::v3::Gic::new(gicd.addr(), gicr.addr()) };
+
+    let last = IntId::spi(31);
+    assert_eq!(gic.try_set_irq_enable(last, true), Ok(()));
+    assert_eq!(gicd.read(0x104), 1 << 31);
+    assert_eq!(gic.try_set_cfg(last, Trigger::Edge), Ok(()));
+    assert_eq!(gic.try_set_priority(last, 0x40), Ok(()));
+
+    for id in [IntId::spi(32), IntId::espi(0)] {
+        let err = Err(GicError::NotImplemented(id));
+        assert_eq!(gic.try_set_irq_enable(id, true), err);
+        assert_eq!(gic.try_set_cfg(id, Trigger::Edge), err);
+        assert_eq!(gic.try_set_priority(id, 0x40), err);
+        assert_eq!(
+            gic.set_routing(id, Routing::Specific(Affinity::default())),
+            err
+        );
+    }
+    assert_eq!(gicd.read(0x108), 0);
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn test_v3_legacy_sgi_pending_sources() {
+    use crate::GicError;
+
+    let mut gicd = MockFrame::new(crate::v3::GICD_FRAME_SIZE);
+    let mut gicr = MockFrame::new(0x20000);
+    // GICD_SPENDSGIR1: SGI 5 pending from CPUs 0 and 3
+    gicd.write(0xf24, 0b1001 << 8);
+    let gic = unsafe { crate::v3::Gic::new(gicd.addr(), gicr.addr()) };
+
+    assert_eq!(gic.sgi_pending_sources(IntId::sgi(5)), Ok(0b1001));
+    assert_eq!(gic.sgi_pending_sources(IntId::sgi(4)), Ok(0));
+    gic.clear_sgi_pending_sources(IntId::sgi(5), 0b1000)
+        .unwrap();
+    assert_eq!(gicd.read(0xf14), 0b1000 << 8);
+
+    // GICD_CTLR: ARE_S and ARE_NS
+    gicd.write(0x0000, 0x30);
+    assert_eq!(
+        gic.sgi_pending_sources(IntId::sgi(5)),
+        Err(GicError::AffinityRoutingEnabled)
+    );
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn test_v3_redistributor_region() {
+    use crate::GicError;
+
+    let mut gicd = MockFrame::new(crate::v3::GICD_FRAME_SIZE);
+    // A GICv4 redistributor with VLPI frames, then a GICv3 one
+    let mut gicr = MockFrame::new(0x60000);
+    gicr.write(0x00008, 1 << 1);
+    gicr.write(0x40008, 1 << 4);
+    gicr.write(0x4000c, 0x0000_0001);
+
+    let gic = unsafe { crate::v3::Gic::new_with_gicr_size(gicd.addr(), gicr.addr(), 0x60000) };
+    assert_eq!(gic.redistributor_stride(), 0x40000);
+    assert_eq!(gic.check_redistributor_region(), Ok(2));
+    assert_eq!(gic.redistributor_index_for_mpidr(0x8000_0001), Some(1));
+
+    // The mapping ends before the frame with GICR_TYPER.Last
+    let gic = unsafe { crate::v3::Gic::new_with_gicr_size(gicd.addr(), gicr.addr(), 0x50000) };
+    assert_eq!(
+        gic.check_redistributor_region(),
+        Err(GicError::RedistributorRegionTooSmall)
+    );
+    assert_eq!(gic.redistributor_index_for_mpidr(0x8000_0001), None);
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn test_v3_active_priorities_count() {
+    use crate::v3::ActivePriorities;
+
+    let mut apr = ActivePriorities::default();
+    assert_eq!(apr.active_count(), 0);
+    apr.ap0r[0] = 0b1;
+    apr.ap1r[0] = (1 << 63) | 0b1010_0000;
+    apr.ap1r[3] = 1 << 31;
+    // The NMI bit of ICC_AP1R0_EL1 is not a priority level
+    assert_eq!(apr.active_count(), 4);
+}
+
+#[test]
+fn test_v2m_frame() {
+    use crate::v2::V2m;
+
+    let mut frame = MockFrame::new(0x1000);
+    // MSI_TYPER: 32 SPIs from ID 64
+    frame.write(0x008, (64 << 16) | 32);
+    let v2m = unsafe { V2m::new(frame.addr(), 0x0802_0000) };
+
+    assert_eq!(v2m.spi_range(), (64, 32));
+    assert!(v2m.contains(IntId::spi(32)));
+    assert!(!v2m.contains(IntId::spi(64)));
+    assert_eq!(v2m.doorbell_address(), 0x0802_0040);
+
+    v2m.send(IntId::spi(40));
+    assert_eq!(frame.read(0x040), 72);
+}
+
+#[test]
+fn test_v2_binary_points() {
+    use crate::v2::InitConfig;
+
+    let (_gicd, gicc, gic) = mock_v2();
+    let mut cpu = gic.cpu_interface();
+
+    cpu.init_current_cpu();
+    // Left at the reset value by default
+    assert_eq!(gicc.read(0x08), 0);
+    assert_eq!(gicc.read(0x1c), 0);
+
+    cpu.init_current_cpu_with_config(&InitConfig {
+        binary_point: Some(2),
+        aliased_binary_point: Some(3),
+        ..Default::default()
+    });
+    assert_eq!(gicc.read(0x08), 2);
+    assert_eq!(gicc.read(0x1c), 3);
+    assert_eq!(cpu.binary_point(), 2);
+    assert_eq!(cpu.aliased_binary_point(), 3);
+
+    cpu.set_aliased_binary_point(0xF);
+    assert_eq!(cpu.aliased_binary_point(), 7);
+}
+
+#[test]
+fn test_v2_route_to_cpus() {
+    use crate::GicError;
+
+    let (mut gicd, _gicc, gic) = mock_v2();
+    // GICD_TYPER: ITLinesNumber = 3, CPUNumber = 3 (four CPU interfaces)
+    gicd.write(0x004, (3 << 5) | 3);
+    assert_eq!(gic.cpu_interface_count(), 4);
+
+    gic.route_to_cpus(IntId::spi(10), &[0, 3]).unwrap();
+    assert_eq!(gicd.read_u8(0x800 + 42), 0b1001);
+    assert_eq!(
+        gic.route_to_cpus(IntId::spi(10), &[1, 4]),
+        Err(GicError::InvalidCpuInterface(4))
+    );
+    assert_eq!(gicd.read_u8(0x800 + 42), 0b1001);
+
+    gic.route_to_all_cpus(IntId::spi(11)).unwrap();
+    assert_eq!(gicd.read_u8(0x800 + 43), 0b1111);
+    assert_eq!(
+        gic.route_to_all_cpus(IntId::ppi(1)),
+        Err(GicError::PrivateInterrupt(IntId::ppi(1)))
+    );
+    assert_eq!(
+        gic.route_to_all_cpus(IntId::spi(96)),
+        Err(GicError::NotImplemented(IntId::spi(96)))
+    );
+}
+
+#[test]
+fn test_cpu_target() {
+    use crate::CpuTarget;
+
+    let cpu = CpuTarget::from_mpidr(0x8000_0003);
+    assert_eq!(cpu.v2_interface(), Some(3));
+    assert_eq!(cpu.to_v2_mask_bit(), Some(0b1000));
+    assert_eq!(CpuTarget::from_mpidr(0x8000_0008).v2_interface(), None);
+
+    let (mut gicd, _gicc, gic) = mock_v2();
+    assert_eq!(gic.current_cpu_interface(), None);
+    // GICD_ITARGETSR0 reads as the mask of the calling CPU
+    gicd.write(0x800, 0x2020_2020);
+    let cpu = CpuTarget::from_mpidr(0x8000_0100)
+        .with_v2_interface(gic.current_cpu_interface().unwrap() as u8);
+    assert_eq!(cpu.to_v2_mask_bit(), Some(1 << 5));
+}
+
+#[cfg(target_arch = "aarch64")]
+#[test]
+fn test_cpu_target_v3_affinity() {
+    use crate::{CpuTarget, v3::Affinity};
+
+    let mpidr = 0x12_8034_0203;
+    assert_eq!(
+        CpuTarget::from_mpidr(mpidr).to_v3_affinity(),
+        Affinity::from_mpidr(mpidr)
+    );
+}
+
+#[test]
+fn test_v2_raw_gicd_access() {
+    let (gicd, _gicc, gic) = mock_v2();
+    assert_eq!(gic.gicd_ptr().as_ptr(), gic.gicd_addr().as_ptr::<u8>());
+
+    unsafe { gic.write_gicd_u32(0xc00, 0x1234_5678) };
+    assert_eq!(gicd.read(0xc00), 0x1234_5678);
+    assert_eq!(unsafe { gic.read_gicd_u32(0x004) }, 3);
+}
+
+#[test]
+#[should_panic(expected = "Invalid GICD register offset: 0x1000")]
+fn test_v2_raw_gicd_out_of_frame() {
+    let (_gicd, _gicc, gic) = mock_v2();
+    unsafe { gic.read_gicd_u32(crate::v2::GICD_FRAME_SIZE) };
+}
+
+#[test]
+fn test_v2_enabled_groups() {
+    use crate::v2::EnabledGroups;
+
+    let (_gicd, _gicc, mut gic) = mock_v2();
+    assert!(!gic.is_enabled());
+
+    gic.init();
+    assert!(gic.is_enabled());
+    assert_eq!(
+        gic.enabled_groups(),
+        EnabledGroups {
+            group0: true,
+            group1: true
+        }
+    );
+}
+
+#[test]
+fn test_v2_set_priorities() {
+    let (gicd, _gicc, gic) = mock_v2();
+
+    gic.set_priorities(30..200, |id| (id.to_u32() % 2 == 0).then_some(0x40));
+    assert_eq!(gicd.read_u8(0x400 + 29), 0);
+    assert_eq!(gicd.read_u8(0x400 + 30), 0x40);
+    assert_eq!(gicd.read_u8(0x400 + 31), 0);
+    assert_eq!(gicd.read_u8(0x400 + 126), 0x40);
+    // Beyond the 128 implemented IDs
+    assert_eq!(gicd.read_u8(0x400 + 128), 0);
+}
+
+#[test]
+fn test_v2_signal_status() {
+    let (mut gicd, _gicc, gic) = mock_v2();
+    // GICD_PPISR: ID 27 asserted
+    gicd.write(0xd00, 1 << 11);
+    // GICD_SPISR1: ID 64 + 5 asserted
+    gicd.write(0xd08, 1 << 5);
+
+    assert_eq!(gic.ppi_status(), 1 << 11);
+    assert_eq!(gic.spi_status(1), 1 << 5);
+    assert!(gic.is_signal_asserted(IntId::ppi(11)));
+    assert!(!gic.is_signal_asserted(IntId::ppi(12)));
+    assert!(gic.is_signal_asserted(IntId::spi(37)));
+    assert!(!gic.is_signal_asserted(IntId::spi(36)));
+    assert!(!gic.is_signal_asserted(IntId::sgi(11)));
+    // Beyond ITLinesNumber
+    assert!(!gic.is_signal_asserted(IntId::spi(200)));
+}
+
+#[test]
+fn test_v2_cpu_shutdown() {
+    let (_gicd, gicc, gic) = mock_v2();
+    let mut cpu = gic.cpu_interface();
+    cpu.init_current_cpu();
+    // GICC_CTLR and GICC_PMR
+    assert_ne!(gicc.read(0x000), 0);
+    assert_eq!(gicc.read(0x004), 0xFF);
+
+    cpu.shutdown();
+    assert_eq!(gicc.read(0x000), 0);
+    assert_eq!(gicc.read(0x004), 0);
+}
+
+#[test]
+fn test_v2_sgi_source_cpu_round_trip() {
+    use crate::v2::Ack;
+
+    let (_gicd, mut gicc, gic) = mock_v2();
+    let cpu = gic.cpu_interface();
+    // GICC_IAR: SGI 3 from CPU 7
+    gicc.write(0x00c, (7 << 10) | 3);
+
+    let ack = cpu.ack();
+    assert!(matches!(ack, Ack::SGI { cpu_id: 7, .. }));
+    cpu.eoi(ack);
+    // GICC_EOIR
+    assert_eq!(gicc.read(0x010), (7 << 10) | 3);
+    cpu.dir(ack);
+    // GICC_DIR
+    assert_eq!(gicc.read(0x1000), (7 << 10) | 3);
+}
+
+#[test]
+#[should_panic(expected = "Invalid SGI source CPU Interface: 8")]
+fn test_v2_sgi_source_cpu_out_of_range() {
+    let (_gicd, _gicc, gic) = mock_v2();
+    gic.cpu_interface().eoi(crate::v2::Ack::SGI {
+        intid: IntId::sgi(3),
+        cpu_id: 8,
+    });
+}
+
+#[test]
+fn test_v2_maintenance_flags() {
+    use crate::v2::{HypervisorInterface, MaintenanceFlags};
+
+    let mut gich = MockFrame::new(0x200);
+    let mut gicv = MockFrame::new(MockFrame::GICC_FRAME_SIZE);
+    // GICH_HCR: En, UIE, EOICount = 3
+    gich.write(0x000, (3 << 27) | 0b11);
+    let hyper = unsafe { HypervisorInterface::new(gich.addr().as_ptr(), gicv.addr().as_ptr()) };
+    assert_eq!(hyper.maintenance_interrupts(), MaintenanceFlags::UNDERFLOW);
+
+    let flags = MaintenanceFlags::NO_PENDING | MaintenanceFlags::VGRP1_DISABLED;
+    hyper.set_maintenance_interrupts(flags);
+    assert_eq!(gich.read(0x000), (3 << 27) | (1 << 7) | (1 << 3) | 1);
+    assert_eq!(hyper.maintenance_interrupts(), flags);
+}
+
+#[test]
+fn test_v2_handle_maintenance() {
+    let mut gich = MockFrame::new(0x200);
+    let mut gicv = MockFrame::new(MockFrame::GICC_FRAME_SIZE);
+    // GICH_MISR: EOI + LRENP
+    gich.write(0x010, 0b101);
+    // GICH_EISR0/1
+    gich.write(0x020, 0b1010);
+    gich.write(0x024, 1);
+
+    let hyper =
+        unsafe { crate::v2::HypervisorInterface::new(gich.addr().as_ptr(), gicv.addr().as_ptr()) };
+    let event = hyper.handle_maintenance();
+    assert!(event.eoi);
+    assert!(event.lr_entry_not_present);
+    assert!(!event.underflow);
+    assert!(!event.no_pending);
+    assert_eq!(
+        event.eoi_list_registers().collect::<std::vec::Vec<_>>(),
+        [1, 3, 32]
+    );
+
+    // Without an EOI cause, EISR is not reported
+    gich.write(0x010, 0b10);
+    let event = hyper.handle_maintenance();
+    assert!(event.underflow);
+    assert_eq!(event.eoi_lrs, 0);
+}
+
+#[test]
+fn test_iidr_decode() {
+    use crate::Iidr;
+
+    let gic500 = Iidr::new(0x0001_143b, 3);
+    assert_eq!(gic500.implementer_name(), Some("Arm"));
+    assert_eq!(gic500.product_name(), Some("GIC-500"));
+    assert_eq!(std::format!("{gic500}"), "Arm GIC-500 r1p1");
+
+    // Same product ID, different architecture
+    assert_eq!(Iidr::new(0x0200_043b, 2).product_name(), Some("GIC-400"));
+    assert_eq!(Iidr::new(0x0200_043b, 3).product_name(), Some("GIC-600"));
+
+    let other = Iidr::new(0x0a00_034c, 3);
+    assert_eq!(other.product_name(), None);
+    assert_eq!(
+        std::format!("{other}"),
+        "implementer 0x34c product 0xa r0p0"
+    );
+}
+
+#[test]
+fn test_v2_iidr() {
+    let (mut gicd, _gicc, gic) = mock_v2();
+    // GICD_IIDR of a GIC-400 r0p1
+    gicd.write(0x008, 0x0200_143b);
+    assert_eq!(std::format!("{}", gic.iidr()), "Arm GIC-400 r0p1");
+}
+
+#[test]
+fn test_intid_classify() {
+    use crate::IntClass;
+
+    let class = |raw| unsafe { IntId::raw(raw) }.classify();
+    assert_eq!(class(0), IntClass::Sgi);
+    assert_eq!(class(31), IntClass::Ppi);
+    assert_eq!(class(1019), IntClass::Spi);
+    assert_eq!(class(1020), IntClass::Special);
+    assert_eq!(class(1023), IntClass::Special);
+    assert_eq!(class(1024), IntClass::Reserved);
+    assert_eq!(class(1056), IntClass::Eppi);
+    assert_eq!(class(1120), IntClass::Reserved);
+    assert_eq!(class(5119), IntClass::Espi);
+    assert_eq!(class(8191), IntClass::Reserved);
+    assert_eq!(class(8192), IntClass::Lpi);
+}
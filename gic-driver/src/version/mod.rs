@@ -13,6 +13,8 @@ mod rdif;
 trait IrqVecWriteable {
     fn set_irq_bit(&self, intid: u32);
     fn clear_irq_bit(&self, intid: u32);
+    /// 为 `start..start + count` 写入 1，每个 32 位寄存器只写一次
+    fn set_irq_bit_range(&self, start: u32, count: u32);
 }
 trait IrqVecReadable {
     fn get_irq_bit(&self, intid: u32) -> bool;
@@ -26,6 +28,14 @@ impl IrqVecWriteable for [ReadWrite<u32>] {
         // Writing 0 has no effect, so we can safely write only the target bit
         self[reg_index].set(bit);
     }
+    fn set_irq_bit_range(&self, start: u32, count: u32) {
+        for (reg_index, mask) in irq_range_masks(start, count) {
+            if reg_index >= self.len() {
+                break;
+            }
+            self[reg_index].set(mask);
+        }
+    }
     fn clear_irq_bit(&self, intid: u32) {
         let reg_index = (intid / 32) as usize;
         let bit = 1 << (intid % 32);
@@ -45,6 +55,40 @@ impl IrqVecReadable for [ReadWrite<u32>] {
     }
 }
 
+/// Split `start..start + count` into `(register index, bit mask)` pairs of
+/// 32-bit interrupt banks, covering partial banks at both edges.
+pub(crate) fn irq_range_masks(start: u32, count: u32) -> impl Iterator<Item = (usize, u32)> {
+    let end = start.saturating_add(count);
+    let regs = if start < end {
+        start / 32..end.div_ceil(32)
+    } else {
+        0..0
+    };
+    regs.map(move |reg| {
+        let lo = start.max(reg * 32) - reg * 32;
+        let hi = end.min(reg * 32 + 32) - reg * 32;
+        let mask = (u32::MAX >> (32 - (hi - lo))) << lo;
+        (reg as usize, mask)
+    })
+}
+
+/// Check whether an interrupt of `priority` would preempt the `running` priority.
+///
+/// The low `subpriority_bits` bits of a priority are subpriority, only the
+/// remaining group priority bits take part in preemption.
+pub(crate) fn would_preempt(priority: u8, running: u8, subpriority_bits: u32) -> bool {
+    let mask = (0xFFu32 << subpriority_bits) as u8;
+    (priority & mask) < (running & mask)
+}
+
+/// Yield the interrupt ID of every set bit in `word`, where bit 0 is `base`.
+#[cfg(any(target_arch = "aarch64", doc))]
+fn iter_irq_bits(word: u32, base: u32) -> impl Iterator<Item = IntId> {
+    (0..32)
+        .filter(move |bit| word & (1 << bit) != 0)
+        .map(move |bit| unsafe { IntId::raw(base + bit) })
+}
+
 /// Parse interrupt configuration from device tree interrupt specifier.
 ///
 /// This function interprets device tree interrupt specifiers according to the
@@ -74,7 +118,7 @@ impl IrqVecReadable for [ReadWrite<u32>] {
 ///
 /// - `0x1`: Edge rising
 /// - `0x2`: Edge falling  
-/// - `0x3`: Edge both
+/// - `0x3`: Edge both, rejected since the GIC can only trigger on one edge
 /// - `0x4`: Level high
 /// - `0x8`: Level low
 ///
@@ -85,9 +129,12 @@ impl IrqVecReadable for [ReadWrite<u32>] {
 ///
 /// # Errors
 ///
-/// - "Invalid IRQ configuration: need at least 3 parameters" - Insufficient parameters
+/// - "Invalid IRQ configuration: need at least 3 parameters" - Empty or 2-element specifier
+/// - "SGI number out of range" - Single-parameter specifier with an SGI number >= 16
 /// - "Invalid IRQ type" - Unknown interrupt type
+/// - "SPI number out of range" / "PPI number out of range" - Number exceeds the SPI/PPI range
 /// - "IRQ_TYPE_NONE is not allowed for IRQ type" - Missing trigger configuration
+/// - "IRQ_TYPE_EDGE_BOTH is not supported by the GIC" - Both-edge trigger requested
 /// - "Invalid IRQ trigger type" - Unknown trigger flags
 ///
 /// # Examples
@@ -106,14 +153,14 @@ impl IrqVecReadable for [ReadWrite<u32>] {
 ///
 /// // PPI interrupt (type=1, number=2, edge-rising)
 /// let ppi_config = fdt_parse_irq_config(&[1, 2, 1]).unwrap();
-/// assert_eq!(spi_config.id.to_u32(), 18); // 16 + 2
+/// assert_eq!(ppi_config.id.to_u32(), 18); // 16 + 2
 /// assert_eq!(ppi_config.trigger, Trigger::Edge);
 /// ```
 pub fn fdt_parse_irq_config(itr: &[u32]) -> Result<IrqConfig, &'static str> {
     // Handle single parameter case (SGI)
-    if itr.len() == 1 && itr[0] < 16 {
+    if itr.len() == 1 {
         return Ok(IrqConfig {
-            id: IntId::sgi(itr[0]),
+            id: IntId::try_sgi(itr[0]).map_err(|_| "SGI number out of range")?,
             trigger: Trigger::Edge, // SGI is always edge-triggered
         });
     }
@@ -152,11 +199,15 @@ pub fn fdt_parse_irq_config(itr: &[u32]) -> Result<IrqConfig, &'static str> {
     let hwirq = match irq_type {
         SPI => {
             // SPI: hwirq = param[1] + 32
-            SPI_RANGE.start + irq_num
+            IntId::try_spi(irq_num)
+                .map_err(|_| "SPI number out of range")?
+                .to_u32()
         }
         PPI => {
             // PPI: hwirq = param[1] + 16
-            PPI_RANGE.start + irq_num
+            IntId::try_ppi(irq_num)
+                .map_err(|_| "PPI number out of range")?
+                .to_u32()
         }
         ESPI => {
             // ESPI: hwirq = param[1] + ESPI_BASE_INTID
@@ -188,7 +239,11 @@ pub fn fdt_parse_irq_config(itr: &[u32]) -> Result<IrqConfig, &'static str> {
 
     // Determine trigger type from flags
     let trigger = match irq_flags {
-        IRQ_TYPE_EDGE_RISING | IRQ_TYPE_EDGE_FALLING | IRQ_TYPE_EDGE_BOTH => Trigger::Edge,
+        IRQ_TYPE_EDGE_RISING | IRQ_TYPE_EDGE_FALLING => Trigger::Edge,
+        IRQ_TYPE_EDGE_BOTH => {
+            // ICFGR has no both-edges mode, see `TriggerError::BothEdgesUnsupported`
+            return Err("IRQ_TYPE_EDGE_BOTH is not supported by the GIC");
+        }
         IRQ_TYPE_LEVEL_HIGH | IRQ_TYPE_LEVEL_LOW => Trigger::Level,
         IRQ_TYPE_NONE if irq_type == PARTITION => {
             // Partitioned PPIs can have IRQ_TYPE_NONE, default to level
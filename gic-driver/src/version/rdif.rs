@@ -26,8 +26,7 @@ impl Interface for super::v2::Gic {
 #[cfg(target_arch = "aarch64")]
 impl DriverGeneric for super::v3::Gic {
     fn open(&mut self) -> Result<(), KError> {
-        self.init();
-        Ok(())
+        self.init().map_err(|_| KError::Io)
     }
 
     fn close(&mut self) -> Result<(), KError> {
@@ -66,15 +65,17 @@ impl From<crate::define::Trigger> for Trigger {
     }
 }
 
-impl From<Trigger> for crate::define::Trigger {
-    fn from(trigger: Trigger) -> Self {
-        match trigger {
+impl TryFrom<Trigger> for crate::define::Trigger {
+    type Error = crate::define::TriggerError;
+
+    fn try_from(trigger: Trigger) -> Result<Self, Self::Error> {
+        Ok(match trigger {
             Trigger::LevelLow => crate::define::Trigger::Level,
             Trigger::LevelHigh => crate::define::Trigger::Level,
             Trigger::EdgeRising => crate::define::Trigger::Edge,
-            Trigger::EdgeBoth => crate::define::Trigger::Edge,
+            Trigger::EdgeBoth => return Err(crate::define::TriggerError::BothEdgesUnsupported),
             Trigger::EdgeFailling => crate::define::Trigger::Edge,
-        }
+        })
     }
 }
 
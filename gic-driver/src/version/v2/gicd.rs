@@ -1,6 +1,6 @@
 use tock_registers::{interfaces::*, register_bitfields, register_structs, registers::*};
 
-use crate::{IntId, define::Trigger};
+use crate::{IntId, define::Trigger, v2::InterruptGroup};
 
 register_structs! {
     #[allow(non_snake_case)]
@@ -88,6 +88,11 @@ impl DistributorReg {
             .modify(CTLR::EnableGrp0::SET + CTLR::EnableGrp1::SET);
     }
 
+    /// Enable a GICv1 Distributor, whose GICD_CTLR only implements bit 0
+    pub fn enable_v1(&self) {
+        self.CTLR.write(CTLR::EnableGrp0::SET);
+    }
+
     /// Disable all interrupts
     pub fn irq_disable_all(&self, max_interrupts: u32) {
         // Calculate number of ICENABLER registers needed
@@ -121,14 +126,18 @@ impl DistributorReg {
         }
     }
 
-    /// Configure interrupt groups - set all interrupts to Group 0 by default
-    pub(crate) fn groups_all_to_0(&self, max_interrupts: u32) {
+    /// Configure interrupt groups - set all interrupts to the same group
+    pub(crate) fn groups_all_to(&self, max_interrupts: u32, group: InterruptGroup) {
         // Calculate number of IGROUPR registers needed
         let num_regs = max_interrupts.div_ceil(32) as usize;
         let num_regs = num_regs.min(self.IGROUPR.len());
+        let val = match group {
+            InterruptGroup::Group0 => 0,
+            InterruptGroup::Group1 => u32::MAX,
+        };
 
         for i in 0..num_regs {
-            self.IGROUPR[i].set(0);
+            self.IGROUPR[i].set(val);
         }
     }
 
@@ -143,15 +152,15 @@ impl DistributorReg {
     }
 
     /// Set default priorities for SPI (ID 32..max_interrupts-1)
-    pub(crate) fn set_default_spi_priorities(&self, max_interrupts: u32) {
-        let total_regs = max_interrupts.div_ceil(4) as usize;
-        let total_regs = total_regs.min(self.IPRIORITYR.len());
+    pub(crate) fn set_default_spi_priorities(&self, max_interrupts: u32, priority: u8) {
+        // IPRIORITYR is byte-accessible, one register per interrupt
+        let total = (max_interrupts as usize).min(self.IPRIORITYR.len());
 
         // SPI starts from interrupt ID 32
         let spi_start_id = 32;
 
-        for i in spi_start_id..total_regs {
-            self.IPRIORITYR[i].set(0xA0);
+        for i in spi_start_id..total {
+            self.IPRIORITYR[i].set(priority);
         }
     }
 
@@ -177,18 +186,31 @@ impl DistributorReg {
     }
 
     /// Configure interrupt configuration (edge/level triggered)
-    pub(crate) fn configure_interrupt_config(&self, max_interrupts: u32) {
+    pub(crate) fn configure_interrupt_config(&self, max_interrupts: u32, trigger: Trigger) {
         // Calculate number of ICFGR registers needed (16 interrupts per register)
         let num_regs = max_interrupts.div_ceil(16) as usize;
-        let num_regs = num_regs.min(self.ICFGR.len());
+        let num_regs = num_regs.min(self.icfgr_count());
 
-        // Configure all interrupts as level-sensitive (0x0) by default
-        // SGIs are always edge-triggered, but we can set the bits anyway
+        // Int_config[1] of every 2-bit field: 0 = level-sensitive, 1 = edge-triggered
+        let spi_val = match trigger {
+            Trigger::Level => 0,
+            Trigger::Edge => 0xAAAA_AAAA,
+        };
+        // SGIs are always edge-triggered, but we can set the bits anyway.
+        // The default trigger only applies to SPIs (ICFGR2 onwards).
+        // Int_config[0] selects the N-N / 1-N model on GICv1 and is reserved
+        // on GICv2, so it is preserved.
         for i in 0..num_regs {
-            self.ICFGR[i].set(0);
+            let model = self.ICFGR[i].get() & 0x5555_5555;
+            self.ICFGR[i].set(model | if i < 2 { 0 } else { spi_val });
         }
     }
 
+    /// Number of implemented ICFGR registers (16 interrupts per register)
+    pub fn icfgr_count(&self) -> usize {
+        (self.max_spi_num().div_ceil(16) as usize).min(self.ICFGR.len())
+    }
+
     pub fn max_spi_num(&self) -> u32 {
         let it_lines_number = self.TYPER.read(TYPER::ITLinesNumber); // ITLinesNumber field
         (it_lines_number + 1) * 32
@@ -200,7 +222,7 @@ impl DistributorReg {
         let bit_offset = (int_num % 16) * 2 + 1; // Each interrupt uses 2 bits, we use bit 1 for edge/level
 
         assert!(
-            reg_index < self.ICFGR.len(),
+            reg_index < self.icfgr_count(),
             "Invalid interrupt ID for config: {id:?}"
         );
 
@@ -221,7 +243,7 @@ impl DistributorReg {
         let bit_offset = (int_num % 16) * 2 + 1; // Each interrupt uses 2 bits, we use bit 1 for edge/level
 
         assert!(
-            reg_index < self.ICFGR.len(),
+            reg_index < self.icfgr_count(),
             "Invalid interrupt ID for config: {id:?}"
         );
 
@@ -1,29 +1,86 @@
-use core::ptr::NonNull;
+use core::{fmt, ops::Range, ptr::NonNull};
 
-use log::trace;
-use tock_registers::{LocalRegisterCopy, interfaces::*};
+use log::{trace, warn};
+use tock_registers::{LocalRegisterCopy, interfaces::*, registers::ReadWrite};
 
 mod gicc;
 mod gicd;
 mod gich;
+mod v2m;
 
 use gicc::CpuInterfaceReg;
 use gicd::DistributorReg;
 use gich::HypervisorRegs;
+use v2m::V2mRegs;
 
-pub use crate::{IntId, VirtAddr, define::Trigger};
+pub use crate::{GicError, Iidr, IntId, IntIdRemap, VirtAddr, define::Trigger};
 
 use crate::version::{IrqVecReadable, IrqVecWriteable};
 
-/// GICv2 driver. (support GICv1)
+/// Size of the GICD register frame
+pub const GICD_FRAME_SIZE: usize = 0x1000;
+
+/// GICv2 driver.
+///
+/// GICv1 is supported through [`Gic::new_v1`] or [`Gic::detect_variant`],
+/// which restrict the driver to the registers and bits GICv1 implements.
 pub struct Gic {
     gicd: VirtAddr,
     gicc: VirtAddr,
     gich: Option<HypervisorInterface>, // Optional for GICv2
+    variant: Variant,
+}
+
+/// Architecture version handled by the [`v2`](self) driver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// GICv1: single enable bit in GICD_CTLR/GICC_CTLR, no GICC_DIR or
+    /// EOImode split, no virtualization extensions, and GICD_IGROUPR only
+    /// with the Security Extensions.
+    V1,
+    /// GICv2
+    V2,
 }
 
 unsafe impl Send for Gic {}
 
+impl fmt::Debug for Gic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gicd = self.gicd();
+        let iidr = gicd.IIDR.extract();
+        f.debug_struct("Gic")
+            .field("gicd", &self.gicd)
+            .field("gicc", &self.gicc)
+            .field("hypervisor", &self.gich.is_some())
+            .field("variant", &self.variant)
+            .field("arch_rev", &gicd.PIDR2.read(gicd::PIDR2::ArchRev))
+            .field(
+                "implementer",
+                &format_args!("{:#x}", iidr.read(gicd::IIDR::Implementer)),
+            )
+            .field("revision", &iidr.read(gicd::IIDR::Revision))
+            .field("max_intid", &self.max_intid())
+            .finish()
+    }
+}
+
+impl fmt::Display for Gic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gicd = self.gicd();
+        let iidr = gicd.IIDR.extract();
+        write!(
+            f,
+            "GICv{} at {:#x}: implementer {:#x} r{}p{}, {} interrupts",
+            gicd.PIDR2.read(gicd::PIDR2::ArchRev),
+            usize::from(self.gicd),
+            iidr.read(gicd::IIDR::Implementer),
+            iidr.read(gicd::IIDR::Variant),
+            iidr.read(gicd::IIDR::Revision),
+            self.max_intid(),
+        )
+    }
+}
+
 pub struct HyperAddress {
     pub gich: VirtAddr,
     pub gicv: VirtAddr,
@@ -51,13 +108,82 @@ impl Gic {
                 }),
                 None => None,
             },
+            variant: Variant::V2,
+        }
+    }
+
+    /// Create a driver for a GICv1 (e.g. the Cortex-A9 MPCore GIC).
+    ///
+    /// GICv1 has a single enable bit in GICD_CTLR and GICC_CTLR, and
+    /// GICD_IGROUPR only exists with the Security Extensions. [`Self::init`]
+    /// therefore leaves interrupt groups untouched, only sets the distributor
+    /// enable bit, and keeps the `Int_config[0]` (N-N / 1-N model) bits of
+    /// GICD_ICFGR. The virtualization extensions are not available.
+    ///
+    /// GICv2-only features such as [`CpuInterface::set_eoi_mode_ns`] and
+    /// interrupt grouping must not be used on a GICv1.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the provided pointers are valid and point to the correct GICv1 registers.
+    pub const unsafe fn new_v1(gicd: VirtAddr, gicc: VirtAddr) -> Self {
+        Self {
+            gicd,
+            gicc,
+            gich: None,
+            variant: Variant::V1,
         }
     }
 
+    /// Whether this driver handles a GICv1, see [`Self::variant`]
+    pub fn is_v1(&self) -> bool {
+        self.variant == Variant::V1
+    }
+
+    /// Get the architecture version the driver restricts itself to
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Detect GICv1 from GICD_PIDR2.ArchRev and adapt the driver to it
+    ///
+    /// Must be called before [`Self::init`] and [`Self::cpu_interface`]. The
+    /// ID registers are implementation defined on GICv1, so on
+    /// implementations that do not report ArchRev 1 use [`Self::new_v1`]
+    /// instead. A GIC detected as GICv1 drops the virtualization interface.
+    pub fn detect_variant(&mut self) -> Variant {
+        if self.gicd().PIDR2.read(gicd::PIDR2::ArchRev) == 1 {
+            self.variant = Variant::V1;
+            self.gich = None;
+        } else {
+            self.variant = Variant::V2;
+        }
+        self.variant
+    }
+
     fn gicd(&self) -> &DistributorReg {
         unsafe { &*(self.gicd.as_ptr()) }
     }
 
+    /// Check whether the distributor forwards any interrupt group
+    pub fn is_enabled(&self) -> bool {
+        let groups = self.enabled_groups();
+        groups.group0 || groups.group1
+    }
+
+    /// Decode the GICD_CTLR group enables
+    ///
+    /// On a GICv1 the single enable bit is reported as `group0`. Without the
+    /// Security Extensions, or from Non-secure state, GICD_CTLR only shows
+    /// one enable bit, which is also reported as `group0`.
+    pub fn enabled_groups(&self) -> EnabledGroups {
+        let ctlr = self.gicd().CTLR.extract();
+        EnabledGroups {
+            group0: ctlr.is_set(gicd::CTLR::EnableGrp0),
+            group1: self.variant != Variant::V1 && ctlr.is_set(gicd::CTLR::EnableGrp1),
+        }
+    }
+
     pub fn gicc_addr(&self) -> VirtAddr {
         self.gicc
     }
@@ -66,10 +192,64 @@ impl Gic {
         self.gicd
     }
 
+    /// Base of the distributor register frame, for registers the typed API
+    /// does not model.
+    pub fn gicd_ptr(&self) -> NonNull<u8> {
+        unsafe { NonNull::new_unchecked(self.gicd.as_ptr()) }
+    }
+
+    /// Read the 32-bit distributor register at byte `offset`.
+    ///
+    /// Escape hatch for implementation-defined registers, e.g. from an
+    /// erratum workaround. The access is a single volatile load.
+    ///
+    /// # Safety
+    ///
+    /// Reads of some registers have side effects. The caller must make sure
+    /// the register at `offset` exists on this implementation and that
+    /// reading it does not interfere with the driver's own use of the GIC.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is not 4-byte aligned or the access does not fit
+    /// the 4 KiB frame ([`GICD_FRAME_SIZE`]).
+    pub unsafe fn read_gicd_u32(&self, offset: usize) -> u32 {
+        let ptr = self.gicd_reg_ptr(offset);
+        unsafe { ptr.read_volatile() }
+    }
+
+    /// Write the 32-bit distributor register at byte `offset`.
+    ///
+    /// Counterpart of [`Self::read_gicd_u32`]; the access is a single
+    /// volatile store.
+    ///
+    /// # Safety
+    ///
+    /// The caller must make sure the register at `offset` exists on this
+    /// implementation and that writing `val` leaves the GIC in a state the
+    /// driver can still operate on.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`Self::read_gicd_u32`].
+    pub unsafe fn write_gicd_u32(&self, offset: usize, val: u32) {
+        let ptr = self.gicd_reg_ptr(offset);
+        unsafe { ptr.write_volatile(val) }
+    }
+
+    fn gicd_reg_ptr(&self, offset: usize) -> *mut u32 {
+        assert!(
+            offset.is_multiple_of(4) && offset + 4 <= GICD_FRAME_SIZE,
+            "Invalid GICD register offset: {offset:#x}"
+        );
+        unsafe { self.gicd.as_ptr::<u8>().add(offset).cast() }
+    }
+
     pub fn cpu_interface(&self) -> CpuInterface {
         CpuInterface {
             gicd: self.gicd.as_ptr(),
             gicc: self.gicc.as_ptr(),
+            variant: self.variant,
         }
     }
 
@@ -82,7 +262,33 @@ impl Gic {
 
     /// Initialize the GIC according to GICv2 specification
     /// This includes both Distributor and CPU Interface initialization
+    ///
+    /// Only the distributor is enabled here. Each CPU must then call
+    /// [`CpuInterface::init_current_cpu`] on its own interface; the
+    /// distributor must be initialized first. For the boot CPU,
+    /// [`Self::init_primary_cpu`] does both in the correct order.
     pub fn init(&mut self) {
+        self.init_with(&InitConfig::default());
+    }
+
+    /// Initialize the distributor like [`Self::init`], with the per-interrupt
+    /// defaults taken from `config`
+    pub fn init_with_config(&mut self, config: InitConfig) {
+        self.init_with(&config);
+    }
+
+    /// Initialize the distributor, then the CPU interface of the boot CPU
+    ///
+    /// Returns the ready CPU interface of the calling CPU. Secondary CPUs
+    /// still need [`CpuInterface::init_current_cpu`].
+    pub fn init_primary_cpu(&mut self) -> CpuInterface {
+        self.init();
+        let mut cpu = self.cpu_interface();
+        cpu.init_current_cpu();
+        cpu
+    }
+
+    fn init_with(&mut self, config: &InitConfig) {
         trace!(
             "Initializing GICv2 Distributor@{:#p}...",
             self.gicd.as_ptr::<u8>()
@@ -102,21 +308,33 @@ impl Gic {
         // 5. Clear all active interrupts
         self.gicd().active_clear_all(max_spi);
 
-        // 6. Configure all interrupts as Group 1 (Non-secure) by default
-        self.gicd().groups_all_to_0(max_spi);
-        trace!("[GICv2] Configure all interrupts as Group 1 (Non-secure) by default");
+        // 6. Configure all interrupts to the default group
+        //    (GICv1 without Security Extensions has no IGROUPR)
+        if self.variant != Variant::V1 {
+            self.gicd().groups_all_to(max_spi, config.default_group);
+            trace!(
+                "[GICv2] Configure all interrupts as {:?} by default",
+                config.default_group
+            );
+        }
 
         // 7. Set default priority for spi interrupts
-        self.gicd().set_default_spi_priorities(max_spi);
+        self.gicd()
+            .set_default_spi_priorities(max_spi, config.default_priority);
 
         // 8. Configure interrupt targets (for SPIs)
         self.gicd().configure_interrupt_targets(max_spi);
         trace!("[GICv2] Configure all SPIs to target cpu 0");
         // 9. Configure interrupt configuration (edge/level trigger)
-        self.gicd().configure_interrupt_config(max_spi);
+        self.gicd()
+            .configure_interrupt_config(max_spi, config.default_trigger);
 
         // 10. Enable the Distributor
-        self.gicd().enable();
+        if self.variant == Variant::V1 {
+            self.gicd().enable_v1();
+        } else {
+            self.gicd().enable();
+        }
     }
 
     /// Set interrupt enable state
@@ -128,28 +346,97 @@ impl Gic {
         }
     }
 
+    /// Enable or disable `count` consecutive interrupts starting at `start`
+    ///
+    /// Writes are coalesced per 32-bit GICD_ISENABLER/ICENABLER bank, so a
+    /// fully covered bank costs a single register write.
+    pub fn set_irq_enable_range(&self, start: IntId, count: u32, enable: bool) {
+        if enable {
+            self.gicd()
+                .ISENABLER
+                .set_irq_bit_range(start.to_u32(), count);
+        } else {
+            self.gicd()
+                .ICENABLER
+                .set_irq_bit_range(start.to_u32(), count);
+        }
+    }
+
     /// Is interrupt enabled?
     pub fn is_irq_enable(&self, id: IntId) -> bool {
         self.gicd().ISENABLER.get_irq_bit(id.into())
     }
 
     /// Set interrupt priority (0 = highest priority, 255 = lowest priority)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not implemented, see [`Self::try_set_priority`].
     pub fn set_priority(&self, id: IntId, priority: u8) {
-        let index = id.to_u32() as usize;
-        assert!(
-            index < self.gicd().IPRIORITYR.len(),
-            "Invalid interrupt ID for priority: {id:?}"
-        );
-        self.gicd().IPRIORITYR[index].set(priority);
+        if let Err(e) = self.try_set_priority(id, priority) {
+            panic!("Invalid interrupt ID for priority: {e}");
+        }
     }
 
+    /// Set interrupt priority, checking that the interrupt is implemented
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::NotImplemented`] if `id` is past
+    /// [`Self::priority_slice`].
+    pub fn try_set_priority(&self, id: IntId, priority: u8) -> Result<(), GicError> {
+        self.priority_slice()
+            .get(id.to_u32() as usize)
+            .ok_or(GicError::NotImplemented(id))?
+            .set(priority);
+        Ok(())
+    }
+
+    /// `GICD_IPRIORITYR<n>` of the implemented interrupts, indexed by interrupt ID
+    ///
+    /// Ends at [`Self::max_intid`], so bulk updates never reach reserved
+    /// registers. Entries 0-31 are banked for the calling CPU.
+    pub fn priority_slice(&self) -> &[ReadWrite<u8>] {
+        &self.gicd().IPRIORITYR[..self.max_intid() as usize]
+    }
+
+    /// Set the priority of every interrupt in `range` for which `f` returns `Some`
+    ///
+    /// IDs past [`Self::max_intid`] are skipped. SGIs and PPIs are banked, so
+    /// their priorities are written for the calling CPU only.
+    pub fn set_priorities<F: Fn(IntId) -> Option<u8>>(&self, range: Range<u32>, f: F) {
+        let end = range.end.min(self.max_intid());
+        for raw in range.start..end {
+            let id = unsafe { IntId::raw(raw) };
+            if let Some(priority) = f(id) {
+                self.gicd().IPRIORITYR[raw as usize].set(priority);
+            }
+        }
+    }
+
+    /// Get interrupt priority
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not implemented, see [`Self::try_get_priority`].
     pub fn get_priority(&self, id: IntId) -> u8 {
-        let index = id.to_u32() as usize;
-        assert!(
-            index < self.gicd().IPRIORITYR.len(),
-            "Invalid interrupt ID for priority: {id:?}"
-        );
-        self.gicd().IPRIORITYR[index].get()
+        match self.try_get_priority(id) {
+            Ok(priority) => priority,
+            Err(e) => panic!("Invalid interrupt ID for priority: {e}"),
+        }
+    }
+
+    /// Get interrupt priority, checking that the interrupt is implemented
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::NotImplemented`] if `id` is past
+    /// [`Self::priority_slice`].
+    pub fn try_get_priority(&self, id: IntId) -> Result<u8, GicError> {
+        self.priority_slice()
+            .get(id.to_u32() as usize)
+            .map(|reg| reg.get())
+            .ok_or(GicError::NotImplemented(id))
     }
 
     /// Set interrupt target CPU for SPIs
@@ -166,6 +453,69 @@ impl Gic {
         self.gicd().ITARGETSR[index].set(target_list.as_u8());
     }
 
+    /// Route an SPI to the CPU interfaces in `cpus`
+    ///
+    /// Builds the GICD_ITARGETSR mask from the CPU interface numbers, so a
+    /// target that does not exist is reported instead of silently never
+    /// receiving the interrupt.
+    ///
+    /// # Errors
+    ///
+    /// - [`GicError::PrivateInterrupt`] if `id` is an SGI or PPI.
+    /// - [`GicError::NotImplemented`] if `id` is past [`Self::max_intid`].
+    /// - [`GicError::InvalidCpuInterface`] if a CPU is not below
+    ///   [`Self::cpu_interface_count`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use arm_gic_driver::{IntId, VirtAddr, v2::Gic};
+    /// # let gic = unsafe { Gic::new(VirtAddr::new(0), VirtAddr::new(0)) };
+    /// gic.route_to_cpus(IntId::spi(10), &[0, 2]).unwrap();
+    /// ```
+    pub fn route_to_cpus(&self, id: IntId, cpus: &[usize]) -> Result<(), GicError> {
+        let count = self.cpu_interface_count();
+        if let Some(&cpu) = cpus.iter().find(|&&cpu| cpu >= count) {
+            return Err(GicError::InvalidCpuInterface(cpu));
+        }
+        self.write_targets(id, TargetList::new(cpus.iter().copied()))
+    }
+
+    /// Route an SPI to every implemented CPU interface
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::route_to_cpus`], apart from the CPU check.
+    pub fn route_to_all_cpus(&self, id: IntId) -> Result<(), GicError> {
+        self.write_targets(id, TargetList::new(0..self.cpu_interface_count()))
+    }
+
+    fn write_targets(&self, id: IntId, targets: TargetList) -> Result<(), GicError> {
+        if id.is_private() {
+            return Err(GicError::PrivateInterrupt(id));
+        }
+        if id.to_u32() >= self.max_intid() {
+            return Err(GicError::NotImplemented(id));
+        }
+        self.gicd().ITARGETSR[id.to_u32() as usize].set(targets.as_u8());
+        Ok(())
+    }
+
+    /// CPU interface number of the calling CPU
+    ///
+    /// GICD_ITARGETSR0 to GICD_ITARGETSR7 are banked and read as the target
+    /// mask of the reading CPU. Returns `None` on a uniprocessor
+    /// implementation, where they read as zero.
+    pub fn current_cpu_interface(&self) -> Option<usize> {
+        let mask = self.gicd().ITARGETSR[0].get();
+        (mask != 0).then(|| mask.trailing_zeros() as usize)
+    }
+
+    /// Number of implemented CPU interfaces (GICD_TYPER.CPUNumber + 1)
+    pub fn cpu_interface_count(&self) -> usize {
+        self.gicd().TYPER.read(gicd::TYPER::CPUNumber) as usize + 1
+    }
+
     pub fn get_target_cpu(&self, id: IntId) -> TargetList {
         assert!(
             !id.is_private(),
@@ -234,14 +584,139 @@ impl Gic {
         self.gicd().ISPENDR.get_irq_bit(id.into())
     }
 
+    /// Clear the pending state of an interrupt in GICD_ICPENDR
+    ///
+    /// For a level-sensitive interrupt this only removes pending state that
+    /// was latched by software; while the device still asserts its line the
+    /// interrupt stays pending. SGI pending state is per source CPU and is
+    /// not cleared here.
+    pub fn clear_pending(&self, id: IntId) {
+        self.set_pending(id, false);
+    }
+
+    /// Disable an interrupt, clear its pending state and enable it again
+    ///
+    /// The interrupt is only re-enabled if it was enabled before, so the
+    /// stale pending state cannot be forwarded to a CPU while it is cleared.
+    /// The level-sensitive caveat of [`Self::clear_pending`] applies.
+    pub fn scrub_pending(&self, id: IntId) {
+        let enabled = self.is_irq_enable(id);
+        if enabled {
+            self.set_irq_enable(id, false);
+        }
+        self.clear_pending(id);
+        if enabled {
+            self.set_irq_enable(id, true);
+        }
+    }
+
+    /// Clear the pending state of `count` consecutive interrupts starting at `start`
+    ///
+    /// Writes whole GICD_ICPENDR banks where the range covers them; other
+    /// interrupts are left untouched. SGI pending state is per source CPU and
+    /// is not cleared through GICD_ICPENDR.
+    pub fn clear_pending_range(&self, start: IntId, count: u32) {
+        self.gicd().ICPENDR.set_irq_bit_range(start.to_u32(), count);
+    }
+
+    /// Clear the active state of `count` consecutive interrupts starting at `start`
+    ///
+    /// Writes whole GICD_ICACTIVER banks where the range covers them; other
+    /// interrupts are left untouched.
+    pub fn clear_active_range(&self, start: IntId, count: u32) {
+        self.gicd()
+            .ICACTIVER
+            .set_irq_bit_range(start.to_u32(), count);
+    }
+
     pub fn gich_ref(&self) -> Option<&HypervisorInterface> {
         self.gich.as_ref()
     }
 
+    /// Get the number of implemented interrupt IDs (GICD_TYPER.ITLinesNumber), capped at 1020
+    pub fn max_intid(&self) -> u32 {
+        self.gicd().max_spi_num().min(1020)
+    }
+
+    /// Number of lockable SPIs (GICD_TYPER.LSPI).
+    ///
+    /// With the Security Extensions a GIC may implement configuration
+    /// lockdown: while the CFGSDISABLE input is asserted, the Secure
+    /// configuration of the first [`Self::lockable_spi_count`] SPIs (IDs 32
+    /// upwards) can no longer be changed. Lockdown is a hardware signal with
+    /// no register to set it, so this only reports the lockable lines.
+    /// Returns 0 if lockdown is not implemented.
+    pub fn lockable_spi_count(&self) -> u32 {
+        if self.gicd().TYPER.read(gicd::TYPER::SecurityExtn) == 0 {
+            return 0;
+        }
+        self.gicd().TYPER.read(gicd::TYPER::LSPI)
+    }
+
+    /// Whether `id` is one of the SPIs affected by configuration lockdown,
+    /// see [`Self::lockable_spi_count`].
+    pub fn is_lockable_spi(&self, id: IntId) -> bool {
+        let id = id.to_u32();
+        (32..32 + self.lockable_spi_count()).contains(&id)
+    }
+
+    /// Number of implemented GICD_ICFGR registers.
+    ///
+    /// Derived from GICD_TYPER.ITLinesNumber; bulk trigger configuration should
+    /// only touch registers below this count.
+    pub fn icfgr_count(&self) -> usize {
+        self.gicd().icfgr_count()
+    }
+
+    /// Read GICD_PPISR, the current input level of the PPIs of this CPU.
+    ///
+    /// Bit `n` corresponds to interrupt ID `16 + n`. The register is
+    /// implementation defined (e.g. GIC-400) and reads as zero if absent.
+    pub fn ppi_status(&self) -> u32 {
+        self.gicd().PPISR.get()
+    }
+
+    /// Read `GICD_SPISR<n>` for `bank`, the current input level of 32 SPIs.
+    ///
+    /// Bit `n` corresponds to interrupt ID `32 + bank * 32 + n`. The register
+    /// is implementation defined (e.g. GIC-400) and reads as zero if absent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bank` is 31 or above.
+    pub fn spi_status(&self, bank: usize) -> u32 {
+        self.gicd().SPISR[bank].get()
+    }
+
+    /// Check whether the peripheral input of `id` is currently asserted.
+    ///
+    /// Useful to diagnose level-sensitive interrupts that do not clear. SGIs
+    /// have no input line and always return `false`.
+    pub fn is_signal_asserted(&self, id: IntId) -> bool {
+        let raw = id.to_u32();
+        if id.is_sgi() || raw >= self.max_intid() {
+            false
+        } else if id.is_private() {
+            self.ppi_status() & (1 << (raw - 16)) != 0
+        } else {
+            let n = raw - 32;
+            self.spi_status((n / 32) as usize) & (1 << (n % 32)) != 0
+        }
+    }
+
     pub fn iidr_raw(&self) -> u32 {
         self.gicd().IIDR.get()
     }
 
+    /// Get the decoded GICD_IIDR, e.g. to print "Arm GIC-400 r0p1"
+    pub fn iidr(&self) -> Iidr {
+        let version = match self.variant {
+            Variant::V1 => 1,
+            Variant::V2 => 2,
+        };
+        Iidr::new(self.iidr_raw(), version)
+    }
+
     pub fn typer_raw(&self) -> u32 {
         self.gicd().TYPER.get()
     }
@@ -265,6 +740,12 @@ pub enum SGITarget {
     Current,
 }
 
+/// Number of CPU interfaces a GICv2 can address.
+///
+/// The source CPU ID in GICC_IAR, GICC_EOIR, GICC_DIR and GICH_LR is a 3-bit
+/// field, and GICD_ITARGETSR / GICD_SGIR hold an 8-bit CPU mask.
+pub const MAX_CPU_INTERFACES: usize = 8;
+
 #[repr(transparent)]
 #[derive(Debug, Clone, Copy)]
 pub struct TargetList(u8);
@@ -274,14 +755,14 @@ impl TargetList {
     pub fn new(list: impl Iterator<Item = usize>) -> Self {
         let mut raw = 0;
         for cpu in list {
-            assert!(cpu < 8, "Invalid CPU Interface: {cpu}");
+            assert!(cpu < MAX_CPU_INTERFACES, "Invalid CPU Interface: {cpu}");
             raw |= 1 << cpu; // Set bit for each target CPU
         }
         Self(raw)
     }
 
     pub fn add(&mut self, cpu: usize) {
-        assert!(cpu < 8, "Invalid CPU Interface: {cpu}");
+        assert!(cpu < MAX_CPU_INTERFACES, "Invalid CPU Interface: {cpu}");
         self.0 |= 1 << cpu; // Set bit for the target CPU
     }
 
@@ -290,7 +771,7 @@ impl TargetList {
     }
 
     pub fn cpu_id_list(&self) -> impl Iterator<Item = usize> {
-        (0..8).filter(move |i| (self.0 & (1 << i)) != 0)
+        (0..MAX_CPU_INTERFACES).filter(move |i| (self.0 & (1 << i)) != 0)
     }
 }
 
@@ -302,11 +783,33 @@ impl SGITarget {
 }
 #[derive(Debug, Clone, Copy)]
 pub enum Ack {
-    SGI { intid: IntId, cpu_id: usize },
+    /// An SGI and the interface ID (`< MAX_CPU_INTERFACES`) of the CPU that
+    /// requested it. The same `cpu_id` must be passed back on EOI/DIR.
+    SGI {
+        intid: IntId,
+        cpu_id: usize,
+    },
     Other(IntId),
 }
 
+/// Check a source CPU ID before it is written to a 3-bit CPUID field, which
+/// would otherwise silently truncate it.
+fn sgi_source_cpu(cpu_id: usize) -> u32 {
+    assert!(
+        cpu_id < MAX_CPU_INTERFACES,
+        "Invalid SGI source CPU Interface: {cpu_id}"
+    );
+    cpu_id as u32
+}
+
 impl Ack {
+    /// The acknowledged interrupt ID
+    pub fn intid(&self) -> IntId {
+        match *self {
+            Ack::SGI { intid, .. } | Ack::Other(intid) => intid,
+        }
+    }
+
     pub fn is_special(&self) -> bool {
         if let Ack::Other(intid) = self {
             intid.is_special()
@@ -321,7 +824,8 @@ impl From<Ack> for u32 {
         match ack {
             Ack::Other(intid) => gicc::IAR::InterruptID.val(intid.to_u32()),
             Ack::SGI { intid, cpu_id } => {
-                gicc::IAR::InterruptID.val(intid.to_u32()) + gicc::IAR::CPUID.val(cpu_id as u32)
+                gicc::IAR::InterruptID.val(intid.to_u32())
+                    + gicc::IAR::CPUID.val(sgi_source_cpu(cpu_id))
             }
         }
         .value
@@ -341,14 +845,150 @@ impl From<u32> for Ack {
     }
 }
 
+/// Distributor group enables, see [`Gic::enabled_groups`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EnabledGroups {
+    /// Group 0 interrupts are forwarded (GICD_CTLR.EnableGrp0)
+    pub group0: bool,
+    /// Group 1 interrupts are forwarded (GICD_CTLR.EnableGrp1)
+    pub group1: bool,
+}
+
+/// Interrupt group of an interrupt (GICD_IGROUPR).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterruptGroup {
+    /// Group 0, Secure with the Security Extensions
+    Group0,
+    /// Group 1, Non-secure with the Security Extensions
+    Group1,
+}
+
+/// Defaults written to every SPI by [`Gic::init_with_config`]
+///
+/// [`Default`] matches [`Gic::init`]: priority `0xA0`, Group 0,
+/// level-triggered. The group is ignored on GICv1, see [`Gic::new_v1`].
+///
+/// The binary points are applied by
+/// [`CpuInterface::init_current_cpu_with_config`]; `None` keeps the reset
+/// value of the register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InitConfig {
+    /// Priority of every SPI
+    pub default_priority: u8,
+    /// Group of every interrupt
+    pub default_group: InterruptGroup,
+    /// Trigger mode of every SPI
+    pub default_trigger: Trigger,
+    /// GICC_BPR of each CPU interface
+    pub binary_point: Option<u8>,
+    /// GICC_ABPR of each CPU interface, the binary point of Group 1
+    /// interrupts. Ignored on a GICv1, where GICC_ABPR is optional.
+    pub aliased_binary_point: Option<u8>,
+}
+
+impl Default for InitConfig {
+    fn default() -> Self {
+        Self {
+            default_priority: 0xA0,
+            default_group: InterruptGroup::Group0,
+            default_trigger: Trigger::Level,
+            binary_point: None,
+            aliased_binary_point: None,
+        }
+    }
+}
+
+/// Builder that creates and initializes a [`Gic`] in one expression.
+///
+/// Settings that are not given keep the behaviour of [`Gic::init`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use arm_gic_driver::{VirtAddr, v2::{GicBuilder, HyperAddress}};
+///
+/// let gic = unsafe { GicBuilder::new(VirtAddr::new(0x0800_0000), VirtAddr::new(0x0801_0000)) }
+///     .hyper(HyperAddress::new(VirtAddr::new(0x0803_0000), VirtAddr::new(0x0804_0000)))
+///     .default_priority(0x80)
+///     .build();
+/// ```
+pub struct GicBuilder {
+    gicd: VirtAddr,
+    gicc: VirtAddr,
+    hyper: Option<HyperAddress>,
+    config: InitConfig,
+}
+
+impl GicBuilder {
+    /// Start building a [`Gic`] for the given distributor and CPU interface.
+    ///
+    /// # Safety
+    ///
+    /// Same requirements as [`Gic::new`].
+    pub unsafe fn new(gicd: VirtAddr, gicc: VirtAddr) -> Self {
+        Self {
+            gicd,
+            gicc,
+            hyper: None,
+            config: InitConfig::default(),
+        }
+    }
+
+    /// Also map the virtualization extension (GICH/GICV) registers.
+    pub fn hyper(mut self, hyper: HyperAddress) -> Self {
+        self.hyper = Some(hyper);
+        self
+    }
+
+    /// Priority written to every SPI during initialization (default `0xA0`).
+    pub fn default_priority(mut self, priority: u8) -> Self {
+        self.config.default_priority = priority;
+        self
+    }
+
+    /// Group every interrupt is placed in during initialization (default Group 0).
+    pub fn default_group(mut self, group: InterruptGroup) -> Self {
+        self.config.default_group = group;
+        self
+    }
+
+    /// Trigger mode written to every SPI during initialization (default level).
+    pub fn default_trigger(mut self, trigger: Trigger) -> Self {
+        self.config.default_trigger = trigger;
+        self
+    }
+
+    /// Create the [`Gic`] and initialize the distributor.
+    ///
+    /// GICv2 initialization has no failure path, so unlike
+    /// [`v3::GicBuilder::build`](crate::v3::GicBuilder::build) this returns
+    /// the `Gic` directly.
+    pub fn build(self) -> Gic {
+        let mut gic = unsafe { Gic::new(self.gicd, self.gicc, self.hyper) };
+        gic.init_with(&self.config);
+        gic
+    }
+}
+
 /// Every CPU interface has its own GICC registers
 pub struct CpuInterface {
     gicd: *mut DistributorReg,
     gicc: *mut CpuInterfaceReg,
+    variant: Variant,
 }
 
 unsafe impl Send for CpuInterface {}
 
+impl fmt::Debug for CpuInterface {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CpuInterface")
+            .field("gicd", &self.gicd)
+            .field("gicc", &self.gicc)
+            .field("variant", &self.variant)
+            .finish()
+    }
+}
+
 impl CpuInterface {
     fn gicc(&self) -> &CpuInterfaceReg {
         unsafe { &*self.gicc }
@@ -360,6 +1000,12 @@ impl CpuInterface {
 
     /// Initialize the CPU interface for the current CPU
     pub fn init_current_cpu(&mut self) {
+        self.init_current_cpu_with_config(&InitConfig::default());
+    }
+
+    /// Initialize the CPU interface for the current CPU, setting the binary
+    /// points given in `config`
+    pub fn init_current_cpu_with_config(&mut self, config: &InitConfig) {
         let gicc = self.gicc();
 
         // 1. Disable CPU interface first
@@ -368,11 +1014,18 @@ impl CpuInterface {
         // 2. Set priority mask to allow all interrupts (lowest priority)
         gicc.PMR.write(gicc::PMR::Priority.val(0xFF));
 
-        // // 3. Set binary point to default value (no preemption)
-        // gicc.BPR.write(BPR::BinaryPoint.val(0x2));
+        // 3. Set binary point
+        if let Some(bp) = config.binary_point {
+            self.set_binary_point(bp);
+        }
 
-        // // 4. Set aliased binary point for Group 1 interrupts
-        // gicc.ABPR.write(ABPR::BinaryPoint.val(0x3));
+        // 4. Set aliased binary point for Group 1 interrupts (optional on GICv1)
+        if let Some(bp) = config
+            .aliased_binary_point
+            .filter(|_| self.variant != Variant::V1)
+        {
+            self.set_aliased_binary_point(bp);
+        }
 
         // 5. Enable CPU interface for both Group 0 and Group 1 interrupts
         gicc.CTLR.write(gicc::CTLR::EnableGrp0::SET);
@@ -380,11 +1033,29 @@ impl CpuInterface {
         // 6. Set default priority for sgi and ppi interrupts
         self.gicd().set_default_sgi_ppi_priorities();
     }
+    /// Disable the CPU interface of the current CPU, e.g. before taking it offline
+    ///
+    /// The inverse of [`Self::init_current_cpu`]: clears GICC_CTLR so no
+    /// interrupt is signalled to the processor and masks all priorities.
+    pub fn shutdown(&mut self) {
+        let gicc = self.gicc();
+        gicc.CTLR.set(0);
+        gicc.PMR.write(gicc::PMR::Priority.val(0));
+    }
+
     /// Set the EOI mode for non-secure interrupts
     ///
     /// - `false` GICC_EOIR has both priority drop and deactivate interrupt functionality. Accesses to the GICC_DIR are UNPREDICTABLE.
     /// - `true`  GICC_EOIR has priority drop functionality only. GICC_DIR has deactivate interrupt functionality.
+    ///
+    /// # Panics
+    ///
+    /// Panics on a GICv1, which has no EOImodeNS bit.
     pub fn set_eoi_mode_ns(&self, is_two_step: bool) {
+        assert!(
+            self.variant != Variant::V1,
+            "EOImodeNS is not implemented on GICv1"
+        );
         if is_two_step {
             self.gicc().CTLR.modify(gicc::CTLR::EOImodeNS::SET);
         } else {
@@ -392,8 +1063,9 @@ impl CpuInterface {
         };
     }
 
+    /// Always `false` on a GICv1
     pub fn eoi_mode_ns(&self) -> bool {
-        self.gicc().CTLR.is_set(gicc::CTLR::EOImodeNS)
+        self.variant != Variant::V1 && self.gicc().CTLR.is_set(gicc::CTLR::EOImodeNS)
     }
 
     /// Acknowledge an interrupt and return the interrupt ID
@@ -403,22 +1075,37 @@ impl CpuInterface {
     }
 
     /// Signal end of interrupt processing
+    ///
+    /// # Panics
+    ///
+    /// Panics if an SGI `cpu_id` is not below [`MAX_CPU_INTERFACES`].
     pub fn eoi(&self, ack: Ack) {
         let val = match ack {
             Ack::Other(intid) => gicc::EOIR::EOIINTID.val(intid.to_u32()),
             Ack::SGI { intid, cpu_id } => {
-                gicc::EOIR::EOIINTID.val(intid.to_u32()) + gicc::EOIR::CPUID.val(cpu_id as u32)
+                gicc::EOIR::EOIINTID.val(intid.to_u32())
+                    + gicc::EOIR::CPUID.val(sgi_source_cpu(cpu_id))
             }
         };
         self.gicc().EOIR.write(val);
     }
 
     /// Deactivate an interrupt
+    ///
+    /// # Panics
+    ///
+    /// Panics if an SGI `cpu_id` is not below [`MAX_CPU_INTERFACES`], or on
+    /// a GICv1, which has no GICC_DIR.
     pub fn dir(&self, ack: Ack) {
+        assert!(
+            self.variant != Variant::V1,
+            "GICC_DIR is not implemented on GICv1"
+        );
         let val = match ack {
             Ack::Other(intid) => gicc::DIR::InterruptID.val(intid.to_u32()),
             Ack::SGI { intid, cpu_id } => {
-                gicc::DIR::InterruptID.val(intid.to_u32()) + gicc::DIR::CPUID.val(cpu_id as u32)
+                gicc::DIR::InterruptID.val(intid.to_u32())
+                    + gicc::DIR::CPUID.val(sgi_source_cpu(cpu_id))
             }
         };
         self.gicc().DIR.write(val);
@@ -436,10 +1123,76 @@ impl CpuInterface {
     }
 
     /// Set the priority mask (interrupts with priority >= mask will be masked)
+    ///
+    /// Unimplemented low-order priority bits are RAZ/WI, so the hardware
+    /// truncates `mask` to the supported precision. For example with 5
+    /// priority bits, a mask of `0x01` becomes `0x00` and masks every
+    /// interrupt. Use [`Self::set_priority_mask_checked`] to see the
+    /// effective value.
     pub fn set_priority_mask(&self, mask: u8) {
         self.gicc().PMR.write(gicc::PMR::Priority.val(mask as u32));
     }
 
+    /// Set the priority mask and return the value the hardware actually holds
+    ///
+    /// If the returned value differs from `mask`, the requested mask was finer
+    /// than the implemented priority bits.
+    pub fn set_priority_mask_checked(&self, mask: u8) -> u8 {
+        self.set_priority_mask(mask);
+        self.gicc().PMR.read(gicc::PMR::Priority) as u8
+    }
+
+    /// Set GICC_BPR, which splits priorities into group priority and subpriority
+    ///
+    /// Only the low three bits of `bp` are used.
+    pub fn set_binary_point(&self, bp: u8) {
+        self.gicc()
+            .BPR
+            .write(gicc::BPR::BinaryPoint.val(bp as u32 & 0x7));
+    }
+
+    /// Get the binary point from GICC_BPR
+    pub fn binary_point(&self) -> u8 {
+        self.gicc().BPR.read(gicc::BPR::BinaryPoint) as u8
+    }
+
+    /// Set GICC_ABPR, the binary point used for Group 1 interrupts
+    ///
+    /// Only the low three bits of `bp` are used.
+    ///
+    /// # Panics
+    ///
+    /// Panics on a GICv1, where GICC_ABPR is only present with the Security
+    /// Extensions.
+    pub fn set_aliased_binary_point(&self, bp: u8) {
+        assert!(
+            self.variant != Variant::V1,
+            "GICC_ABPR is optional on GICv1"
+        );
+        self.gicc()
+            .ABPR
+            .write(gicc::ABPR::BinaryPoint.val(bp as u32 & 0x7));
+    }
+
+    /// Get the Group 1 binary point from GICC_ABPR
+    ///
+    /// Always `0` on a GICv1
+    pub fn aliased_binary_point(&self) -> u8 {
+        if self.variant == Variant::V1 {
+            return 0;
+        }
+        self.gicc().ABPR.read(gicc::ABPR::BinaryPoint) as u8
+    }
+
+    /// Check whether a Group 0 interrupt of `priority` would preempt the running priority
+    ///
+    /// Only the group priority bits selected by GICC_BPR take part in the
+    /// comparison; with BPR = n, bits `[n:0]` are subpriority.
+    pub fn would_preempt(&self, priority: u8) -> bool {
+        let bpr = self.gicc().BPR.read(gicc::BPR::BinaryPoint);
+        crate::version::would_preempt(priority, self.get_running_priority(), bpr + 1)
+    }
+
     pub fn set_irq_enable(&self, id: IntId, enable: bool) {
         assert!(
             id.is_private(),
@@ -535,29 +1288,91 @@ impl CpuInterface {
         self.gicd().get_cfg(id)
     }
 
+    /// Save the active priorities registers (GICC_APRn and GICC_NSAPRn)
+    ///
+    /// These must be saved before a context switch or CPU power-down that
+    /// loses the CPU interface priority state, and written back with
+    /// [`Self::restore_active_priorities`] before interrupts are handled again.
+    pub fn save_active_priorities(&self) -> ActivePriorities {
+        let gicc = self.gicc();
+        ActivePriorities {
+            apr: core::array::from_fn(|i| gicc.APR[i].get()),
+            nsapr: core::array::from_fn(|i| gicc.NSAPR[i].get()),
+        }
+    }
+
+    /// Restore the active priorities registers saved by [`Self::save_active_priorities`]
+    pub fn restore_active_priorities(&self, saved: &ActivePriorities) {
+        let gicc = self.gicc();
+        for (reg, val) in gicc.APR.iter().zip(saved.apr) {
+            reg.set(val);
+        }
+        for (reg, val) in gicc.NSAPR.iter().zip(saved.nsapr) {
+            reg.set(val);
+        }
+    }
+
+    /// Get a lock-free handle for the interrupt handler fast path
+    ///
+    /// See [`TrapOp`].
     pub const fn trap_operations(&self) -> TrapOp {
-        TrapOp::new(self.gicc as *mut u8)
+        TrapOp {
+            gicc: self.gicc,
+            variant: self.variant,
+        }
     }
 }
 
+/// Saved state of the GICv2 CPU interface active priorities registers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ActivePriorities {
+    /// GICC_APR0-3
+    pub apr: [u32; 4],
+    /// GICC_NSAPR0-3
+    pub nsapr: [u32; 4],
+}
+
+/// Acknowledge/EOI/deactivate handle of one CPU interface, from
+/// [`CpuInterface::trap_operations`]
+///
+/// Only touches the banked GICC_IAR/EOIR/DIR registers, so a copy can be
+/// kept in a static and used from the interrupt handler without locking the
+/// [`CpuInterface`]. Like the GICC frame itself it acts on the CPU executing
+/// it.
+#[derive(Debug, Clone, Copy)]
 pub struct TrapOp {
     gicc: *mut CpuInterfaceReg,
+    variant: Variant,
 }
 
 unsafe impl Send for TrapOp {}
 unsafe impl Sync for TrapOp {}
 
 impl TrapOp {
-    const fn new(gicc: *mut u8) -> Self {
-        Self { gicc: gicc as _ }
-    }
-
     fn gicc(&self) -> &CpuInterfaceReg {
         unsafe { &*self.gicc }
     }
 
+    /// Whether completion needs [`Self::dir`] after [`Self::eoi`]
+    ///
+    /// Same as [`Self::eoi_mode_ns`], named after the v3 `TrapOp::eoi_mode`.
+    pub fn eoi_mode(&self) -> bool {
+        self.eoi_mode_ns()
+    }
+
+    /// Always `false` on a GICv1
     pub fn eoi_mode_ns(&self) -> bool {
-        self.gicc().CTLR.is_set(gicc::CTLR::EOImodeNS)
+        self.variant != Variant::V1 && self.gicc().CTLR.is_set(gicc::CTLR::EOImodeNS)
+    }
+
+    /// Get the current priority mask (GICC_PMR)
+    pub fn priority_mask(&self) -> u8 {
+        self.gicc().PMR.read(gicc::PMR::Priority) as u8
+    }
+
+    /// Get the current running priority (GICC_RPR)
+    pub fn running_priority(&self) -> u8 {
+        (self.gicc().RPR.get() & 0xFF) as u8
     }
 
     /// Acknowledge an interrupt and return the interrupt ID
@@ -567,26 +1382,149 @@ impl TrapOp {
     }
 
     /// Signal end of interrupt processing
+    ///
+    /// # Panics
+    ///
+    /// Panics if an SGI `cpu_id` is not below [`MAX_CPU_INTERFACES`].
     pub fn eoi(&self, ack: Ack) {
         let val = match ack {
             Ack::Other(intid) => gicc::EOIR::EOIINTID.val(intid.to_u32()),
             Ack::SGI { intid, cpu_id } => {
-                gicc::EOIR::EOIINTID.val(intid.to_u32()) + gicc::EOIR::CPUID.val(cpu_id as u32)
+                gicc::EOIR::EOIINTID.val(intid.to_u32())
+                    + gicc::EOIR::CPUID.val(sgi_source_cpu(cpu_id))
             }
         };
         self.gicc().EOIR.write(val);
     }
 
     /// Deactivate an interrupt
+    ///
+    /// # Panics
+    ///
+    /// Panics if an SGI `cpu_id` is not below [`MAX_CPU_INTERFACES`], or on
+    /// a GICv1, which has no GICC_DIR.
     pub fn dir(&self, ack: Ack) {
+        assert!(
+            self.variant != Variant::V1,
+            "GICC_DIR is not implemented on GICv1"
+        );
         let val = match ack {
             Ack::Other(intid) => gicc::DIR::InterruptID.val(intid.to_u32()),
             Ack::SGI { intid, cpu_id } => {
-                gicc::DIR::InterruptID.val(intid.to_u32()) + gicc::DIR::CPUID.val(cpu_id as u32)
+                gicc::DIR::InterruptID.val(intid.to_u32())
+                    + gicc::DIR::CPUID.val(sgi_source_cpu(cpu_id))
             }
         };
         self.gicc().DIR.write(val);
     }
+
+    /// Service every pending interrupt with `handler`.
+    ///
+    /// Acknowledges through GICC_IAR, calls `handler` with the [`Ack`], then
+    /// completes it with GICC_EOIR and, when [`Self::eoi_mode`] is set,
+    /// GICC_DIR. This repeats until a special interrupt ID (e.g. 1023,
+    /// spurious) is read. The handler receives the whole [`Ack`] since an
+    /// SGI's source CPU is part of the acknowledge value; use [`Ack::intid`]
+    /// for the ID alone. `handler` returns whether it handled the interrupt;
+    /// an unhandled interrupt is logged and still completed so that it does
+    /// not stay active.
+    ///
+    /// Returns the number of interrupts acknowledged.
+    pub fn dispatch<F: FnMut(Ack) -> bool>(&self, mut handler: F) -> usize {
+        let two_step = self.eoi_mode();
+        let mut serviced = 0;
+        loop {
+            let ack = self.ack();
+            if ack.is_special() {
+                return serviced;
+            }
+            if !handler(ack) {
+                warn!("Unhandled interrupt: {ack:?}");
+            }
+            self.eoi(ack);
+            if two_step {
+                self.dir(ack);
+            }
+            serviced += 1;
+        }
+    }
+}
+
+/// GICv2m MSI frame, which turns MSI writes into SPIs
+///
+/// A PCIe device is programmed with [`Self::doorbell_address`] as MSI address
+/// and an SPI ID from [`Self::spi_range`] as MSI data; the frame then sets
+/// that SPI pending in the distributor. The SPIs are configured through
+/// [`Gic`] like any other SPI and should be edge-triggered.
+///
+/// # Examples
+///
+/// ```no_run
+/// use arm_gic_driver::{VirtAddr, v2::V2m};
+///
+/// // "arm,gic-v2m-frame" node at physical 0x0802_0000, mapped at the same address
+/// let v2m = unsafe { V2m::new(VirtAddr::new(0x0802_0000), 0x0802_0000) };
+/// let (first, count) = v2m.spi_range();
+/// let doorbell = v2m.doorbell_address();
+/// ```
+pub struct V2m {
+    regs: *mut V2mRegs,
+    phys: u64,
+}
+
+unsafe impl Send for V2m {}
+unsafe impl Sync for V2m {}
+
+impl V2m {
+    /// Create a handle for the v2m frame mapped at `base`
+    ///
+    /// `phys` is the physical address of the frame, which devices need to
+    /// reach the doorbell.
+    ///
+    /// # Safety
+    ///
+    /// `base` must map the 4KiB v2m frame located at `phys` for the lifetime
+    /// of the handle.
+    pub const unsafe fn new(base: VirtAddr, phys: u64) -> Self {
+        Self {
+            regs: base.as_ptr(),
+            phys,
+        }
+    }
+
+    fn regs(&self) -> &V2mRegs {
+        unsafe { &*self.regs }
+    }
+
+    /// First SPI interrupt ID and number of SPIs assigned to the frame (MSI_TYPER)
+    pub fn spi_range(&self) -> (u32, u32) {
+        let typer = self.regs().MSI_TYPER.extract();
+        (
+            typer.read(v2m::MSI_TYPER::Base),
+            typer.read(v2m::MSI_TYPER::Number),
+        )
+    }
+
+    /// Check whether `id` is one of the SPIs assigned to the frame
+    pub fn contains(&self, id: IntId) -> bool {
+        let (first, count) = self.spi_range();
+        (first..first + count).contains(&id.to_u32())
+    }
+
+    /// Physical address of MSI_SETSPI_NS, the MSI address for devices
+    pub fn doorbell_address(&self) -> u64 {
+        self.phys + 0x040
+    }
+
+    /// Raise `id` through the doorbell, as an MSI write from a device would
+    pub fn send(&self, id: IntId) {
+        self.regs().MSI_SETSPI_NS.set(id.to_u32());
+    }
+
+    /// Read MSI_IIDR
+    pub fn iidr(&self) -> u32 {
+        self.regs().MSI_IIDR.get()
+    }
 }
 
 /// GIC Hypervisor Interface for virtualization support
@@ -711,9 +1649,33 @@ impl HypervisorInterface {
         }
     }
 
+    /// Set all maintenance interrupt enables of GICH_HCR in one write
+    ///
+    /// Enables not in `flags` are cleared; `En` and `EOICount` are preserved.
+    pub fn set_maintenance_interrupts(&self, flags: MaintenanceFlags) {
+        let hcr = self.gich().HCR.get();
+        self.gich()
+            .HCR
+            .set((hcr & !MaintenanceFlags::all().bits()) | flags.bits());
+    }
+
+    /// Get the maintenance interrupt enables of GICH_HCR
+    pub fn maintenance_interrupts(&self) -> MaintenanceFlags {
+        MaintenanceFlags::from_bits_truncate(self.gich().HCR.get())
+    }
+
     /// Set a virtual interrupt in a list register
-    pub fn set_virtual_interrupt(&self, lr_index: usize, config: VirtualInterruptConfig) {
-        assert!(lr_index < 64, "Invalid list register index");
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::InvalidListRegister`] if `lr_index` is not below
+    /// [`Self::get_list_register_count`].
+    pub fn set_virtual_interrupt(
+        &self,
+        lr_index: usize,
+        config: VirtualInterruptConfig,
+    ) -> Result<(), GicError> {
+        self.check_list_register(lr_index)?;
 
         let mut lr_val = gich::LR::VirtualID.val(config.virtual_id.to_u32())
             + gich::LR::Priority.val(config.priority as u32)
@@ -735,7 +1697,7 @@ impl HypervisorInterface {
                 if let Some(cpu_id) = cpu_id
                     && config.virtual_id.is_sgi()
                 {
-                    lr_val += gich::LR::CPUID.val(cpu_id as u32);
+                    lr_val += gich::LR::CPUID.val(sgi_source_cpu(cpu_id));
                 }
                 if eoi_maintenance {
                     lr_val += gich::LR::EOI::SET;
@@ -744,11 +1706,82 @@ impl HypervisorInterface {
         }
 
         self.gich().LR[lr_index].write(lr_val);
+        Ok(())
+    }
+
+    /// Reject list register indices beyond GICH_VTR.ListRegs, whose GICH_LRn
+    /// slots are reserved and silently ignore writes.
+    fn check_list_register(&self, lr_index: usize) -> Result<(), GicError> {
+        if lr_index < self.get_list_register_count() {
+            Ok(())
+        } else {
+            Err(GicError::InvalidListRegister(lr_index))
+        }
+    }
+
+    /// Inject the physical interrupt `physical` into the guest as a hardware interrupt
+    ///
+    /// The list register's virtual ID is taken from `remap`, while its
+    /// physical ID is `physical`, so the guest's deactivation is forwarded to
+    /// the right physical interrupt.
+    ///
+    /// # Errors
+    ///
+    /// - [`GicError::NotMapped`] if `physical` has no entry in `remap`.
+    /// - [`GicError::InvalidListRegister`] if `lr_index` is not implemented.
+    pub fn inject<const N: usize>(
+        &self,
+        lr_index: usize,
+        physical: IntId,
+        priority: u8,
+        group1: bool,
+        remap: &IntIdRemap<N>,
+    ) -> Result<(), GicError> {
+        let virtual_id = remap
+            .to_virtual(physical)
+            .ok_or(GicError::NotMapped(physical))?;
+        self.set_virtual_interrupt(
+            lr_index,
+            VirtualInterruptConfig::new(
+                virtual_id,
+                priority,
+                VirtualInterruptState::Pending,
+                group1,
+                VirtualInterruptType::hardware(physical.to_u32()),
+            ),
+        )
+    }
+
+    /// Get the host physical interrupt behind a list register
+    ///
+    /// Hardware list registers carry the physical ID themselves; for software
+    /// entries the guest ID is translated through `remap`. Returns `None`
+    /// if `lr_index` is not implemented.
+    pub fn get_physical_interrupt<const N: usize>(
+        &self,
+        lr_index: usize,
+        remap: &IntIdRemap<N>,
+    ) -> Option<IntId> {
+        let config = self.get_virtual_interrupt(lr_index).ok()?;
+        match config.interrupt_type {
+            VirtualInterruptType::Hardware { physical_id } => {
+                Some(unsafe { IntId::raw(physical_id) })
+            }
+            VirtualInterruptType::Software { .. } => remap.to_physical(config.virtual_id),
+        }
     }
 
     /// Get a virtual interrupt configuration from a list register
-    pub fn get_virtual_interrupt(&self, lr_index: usize) -> VirtualInterruptConfig {
-        assert!(lr_index < 64, "Invalid list register index");
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::InvalidListRegister`] if `lr_index` is not below
+    /// [`Self::get_list_register_count`].
+    pub fn get_virtual_interrupt(
+        &self,
+        lr_index: usize,
+    ) -> Result<VirtualInterruptConfig, GicError> {
+        self.check_list_register(lr_index)?;
 
         let lr_val = self.gich().LR[lr_index].extract();
 
@@ -790,13 +1823,13 @@ impl HypervisorInterface {
             }
         };
 
-        VirtualInterruptConfig {
+        Ok(VirtualInterruptConfig {
             virtual_id,
             priority,
             state,
             group1,
             interrupt_type,
-        }
+        })
     }
 
     /// Check if a list register is empty (invalid state)
@@ -820,6 +1853,85 @@ impl HypervisorInterface {
         Ok(())
     }
 
+    /// Save the virtual active priorities (GICH_APR, aliased as GICV_APR0)
+    ///
+    /// Must be saved together with the list registers when switching vCPUs.
+    pub fn save_active_priorities(&self) -> u32 {
+        self.gich().APR.get()
+    }
+
+    /// Restore the virtual active priorities saved by [`Self::save_active_priorities`]
+    pub fn restore_active_priorities(&self, apr: u32) {
+        self.gich().APR.set(apr);
+    }
+
+    /// Get the number of virtual priority bits implemented (GICH_VTR.PRIbits + 1)
+    pub fn virtual_priority_bits(&self) -> u32 {
+        self.gich().VTR.read(gich::VTR::PRIbits) + 1
+    }
+
+    /// Set the guest's virtual priority mask (GICV_PMR, held in GICH_VMCR.VMPriMask)
+    ///
+    /// `priority` is the 8-bit priority as seen by the guest. Priority bits
+    /// that are not implemented (see [`Self::virtual_priority_bits`]) are
+    /// cleared before the value is stored.
+    pub fn set_guest_priority_mask(&self, priority: u8) {
+        let mask = vmcr_pri_mask(priority, self.virtual_priority_bits());
+        self.gich().VMCR.modify(gich::VMCR::VMPriMask.val(mask));
+    }
+
+    /// Get the guest's virtual priority mask as an 8-bit priority
+    pub fn guest_priority_mask(&self) -> u8 {
+        (self.gich().VMCR.read(gich::VMCR::VMPriMask) << 3) as u8
+    }
+
+    /// Write the virtual machine control register (GICH_VMCR) of the current vCPU
+    ///
+    /// The priority mask is handled as in [`Self::set_guest_priority_mask`].
+    pub fn set_vmcr(&self, vmcr: VmcrConfig) {
+        let mut val = gich::VMCR::VMABP.val(vmcr.abpr as u32)
+            + gich::VMCR::VMBP.val(vmcr.bpr as u32)
+            + gich::VMCR::VMPriMask.val(vmcr_pri_mask(
+                vmcr.priority_mask,
+                self.virtual_priority_bits(),
+            ));
+        if vmcr.grp0_enable {
+            val += gich::VMCR::VMGrp0En::SET;
+        }
+        if vmcr.grp1_enable {
+            val += gich::VMCR::VMGrp1En::SET;
+        }
+        if vmcr.ack_ctl {
+            val += gich::VMCR::VMAckCtl::SET;
+        }
+        if vmcr.fiq_enable {
+            val += gich::VMCR::VMFIQEn::SET;
+        }
+        if vmcr.cbpr {
+            val += gich::VMCR::VMCBPR::SET;
+        }
+        if vmcr.eoi_mode {
+            val += gich::VMCR::VEM::SET;
+        }
+        self.gich().VMCR.write(val);
+    }
+
+    /// Read the virtual machine control register (GICH_VMCR) of the current vCPU
+    pub fn get_vmcr(&self) -> VmcrConfig {
+        let vmcr = self.gich().VMCR.extract();
+        VmcrConfig {
+            grp0_enable: vmcr.is_set(gich::VMCR::VMGrp0En),
+            grp1_enable: vmcr.is_set(gich::VMCR::VMGrp1En),
+            ack_ctl: vmcr.is_set(gich::VMCR::VMAckCtl),
+            fiq_enable: vmcr.is_set(gich::VMCR::VMFIQEn),
+            cbpr: vmcr.is_set(gich::VMCR::VMCBPR),
+            eoi_mode: vmcr.is_set(gich::VMCR::VEM),
+            abpr: vmcr.read(gich::VMCR::VMABP) as u8,
+            bpr: vmcr.read(gich::VMCR::VMBP) as u8,
+            priority_mask: (vmcr.read(gich::VMCR::VMPriMask) << 3) as u8,
+        }
+    }
+
     /// Get the maintenance interrupt status
     pub fn get_maintenance_status(&self) -> u32 {
         self.gich().MISR.get()
@@ -840,6 +1952,32 @@ impl HypervisorInterface {
         (self.gich().ELRSR0.get(), self.gich().ELRSR1.get())
     }
 
+    /// Decode the pending maintenance interrupt causes (GICH_MISR)
+    ///
+    /// For an EOI maintenance interrupt, the list registers that signalled
+    /// EOI are taken from GICH_EISR0/1 so they can be recycled.
+    pub fn handle_maintenance(&self) -> MaintenanceEvent {
+        let misr = self.gich().MISR.extract();
+        let eoi = misr.is_set(gich::MISR::EOI);
+        let eoi_lrs = if eoi {
+            let (eisr0, eisr1) = self.get_eoi_status();
+            eisr0 as u64 | ((eisr1 as u64) << 32)
+        } else {
+            0
+        };
+        MaintenanceEvent {
+            eoi,
+            underflow: misr.is_set(gich::MISR::U),
+            lr_entry_not_present: misr.is_set(gich::MISR::LRENP),
+            no_pending: misr.is_set(gich::MISR::NP),
+            vgrp0_enabled: misr.is_set(gich::MISR::VGrp0E),
+            vgrp0_disabled: misr.is_set(gich::MISR::VGrp0D),
+            vgrp1_enabled: misr.is_set(gich::MISR::VGrp1E),
+            vgrp1_disabled: misr.is_set(gich::MISR::VGrp1D),
+            eoi_lrs,
+        }
+    }
+
     pub fn gicv_aiar(&self) -> Option<Ack> {
         let data = self.gicv().AIAR.extract();
         let id = data.read(gicc::AIAR::InterruptID);
@@ -850,6 +1988,94 @@ impl HypervisorInterface {
     }
 }
 
+bitflags::bitflags! {
+    /// Maintenance interrupt enables of GICH_HCR, see
+    /// [`HypervisorInterface::set_maintenance_interrupts`].
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct MaintenanceFlags: u32 {
+        /// Underflow, zero or one list register holds a valid interrupt
+        const UNDERFLOW = 1 << 1;
+        /// The guest deactivated an interrupt that is not in a list register
+        const LR_ENTRY_NOT_PRESENT = 1 << 2;
+        /// No list register is in the pending state
+        const NO_PENDING = 1 << 3;
+        /// The guest enabled virtual Group 0
+        const VGRP0_ENABLED = 1 << 4;
+        /// The guest disabled virtual Group 0
+        const VGRP0_DISABLED = 1 << 5;
+        /// The guest enabled virtual Group 1
+        const VGRP1_ENABLED = 1 << 6;
+        /// The guest disabled virtual Group 1
+        const VGRP1_DISABLED = 1 << 7;
+    }
+}
+
+/// Maintenance interrupt causes, see [`HypervisorInterface::handle_maintenance`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MaintenanceEvent {
+    /// A list register with EOI set was deactivated by the guest
+    pub eoi: bool,
+    /// Zero or one list register holds a valid interrupt (underflow)
+    pub underflow: bool,
+    /// The guest deactivated an interrupt that is not in a list register
+    pub lr_entry_not_present: bool,
+    /// No list register is in the pending state
+    pub no_pending: bool,
+    /// The guest enabled virtual Group 0
+    pub vgrp0_enabled: bool,
+    /// The guest disabled virtual Group 0
+    pub vgrp0_disabled: bool,
+    /// The guest enabled virtual Group 1
+    pub vgrp1_enabled: bool,
+    /// The guest disabled virtual Group 1
+    pub vgrp1_disabled: bool,
+    /// Bitmap of the list registers that signalled EOI (GICH_EISR0/1)
+    pub eoi_lrs: u64,
+}
+
+impl MaintenanceEvent {
+    /// Indices of the list registers that signalled EOI
+    pub fn eoi_list_registers(&self) -> impl Iterator<Item = usize> + use<> {
+        let lrs = self.eoi_lrs;
+        (0..64).filter(move |lr| lrs & (1 << lr) != 0)
+    }
+}
+
+/// Decoded GICH_VMCR, the vCPU's view of its virtual CPU interface control state.
+///
+/// Save with [`HypervisorInterface::get_vmcr`] and restore with
+/// [`HypervisorInterface::set_vmcr`] on vCPU context switches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VmcrConfig {
+    /// Virtual Group 0 interrupts enabled (GICV_CTLR.EnableGrp0)
+    pub grp0_enable: bool,
+    /// Virtual Group 1 interrupts enabled (GICV_CTLR.EnableGrp1)
+    pub grp1_enable: bool,
+    /// GICV_CTLR.AckCtl
+    pub ack_ctl: bool,
+    /// Group 0 virtual interrupts signalled as vFIQ (GICV_CTLR.FIQEn)
+    pub fiq_enable: bool,
+    /// Common binary point (GICV_CTLR.CBPR)
+    pub cbpr: bool,
+    /// Two-step EOI mode (GICV_CTLR.EOImode)
+    pub eoi_mode: bool,
+    /// Aliased binary point (GICV_ABPR)
+    pub abpr: u8,
+    /// Binary point (GICV_BPR)
+    pub bpr: u8,
+    /// 8-bit virtual priority mask (GICV_PMR)
+    pub priority_mask: u8,
+}
+
+/// Convert an 8-bit guest priority to the GICH_VMCR.VMPriMask field.
+///
+/// VMPriMask holds priority bits [7:3]; only the top `pri_bits` bits of the
+/// priority are implemented.
+pub(crate) fn vmcr_pri_mask(priority: u8, pri_bits: u32) -> u32 {
+    let implemented = !(0xFFu32 >> pri_bits.min(5)) & 0xFF;
+    (priority as u32 & implemented) >> 3
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct VirtualInterruptConfig {
     pub virtual_id: IntId,
@@ -0,0 +1,29 @@
+use tock_registers::{register_bitfields, register_structs, registers::*};
+
+register_structs! {
+    /// GICv2m MSI frame registers.
+    #[allow(non_snake_case)]
+    pub V2mRegs {
+        (0x000 => _reserved_0),
+        /// MSI Type Register
+        (0x008 => pub MSI_TYPER: ReadOnly<u32, MSI_TYPER::Register>),
+        (0x00c => _reserved_1),
+        /// Non-secure SPI Set Register, the MSI doorbell
+        (0x040 => pub MSI_SETSPI_NS: WriteOnly<u32>),
+        (0x044 => _reserved_2),
+        /// Interface Identification Register
+        (0xfcc => pub MSI_IIDR: ReadOnly<u32>),
+        (0xfd0 => _reserved_3),
+        (0x1000 => @END),
+    }
+}
+
+register_bitfields! [u32,
+    /// MSI Type Register
+    pub MSI_TYPER [
+        /// Number of SPIs assigned to the frame
+        Number OFFSET(0) NUMBITS(10) [],
+        /// Interrupt ID of the first SPI assigned to the frame
+        Base OFFSET(16) NUMBITS(10) [],
+    ],
+];
@@ -1,14 +1,47 @@
 use core::hint::spin_loop;
 
 use aarch64_cpu::asm::barrier;
-use tock_registers::{interfaces::*, register_bitfields, register_structs, registers::*};
+use tock_registers::{
+    LocalRegisterCopy, interfaces::*, register_bitfields, register_structs, registers::*,
+};
 
 use crate::{
-    IntId,
+    GicError, IntId,
     define::{SPI_RANGE, Trigger},
-    v3::Affinity,
+    v3::{Affinity, InitConfig, InterruptGroup},
 };
 
+/// GICD_IROUTER value for `aff`, or 1-of-N routing when `None`
+pub(crate) fn encode_route(aff: Option<Affinity>) -> LocalRegisterCopy<u64, IROUTER::Register> {
+    let value = match aff {
+        Some(aff) => {
+            IROUTER::Aff0.val(aff.aff0 as u64)
+                + IROUTER::Aff1.val(aff.aff1 as u64)
+                + IROUTER::Aff2.val(aff.aff2 as u64)
+                + IROUTER::Aff3.val(aff.aff3 as u64)
+                + IROUTER::Interrupt_Routing_Mode::Specific
+        }
+        None => IROUTER::Interrupt_Routing_Mode::Any,
+    };
+    LocalRegisterCopy::new(value.value)
+}
+
+/// Inverse of [`encode_route`]
+pub(crate) fn decode_route(reg: LocalRegisterCopy<u64, IROUTER::Register>) -> Option<Affinity> {
+    match reg.read_as_enum(IROUTER::Interrupt_Routing_Mode) {
+        Some(IROUTER::Interrupt_Routing_Mode::Value::Any) => None,
+        _ => Some(Affinity {
+            aff0: reg.read(IROUTER::Aff0) as u8,
+            aff1: reg.read(IROUTER::Aff1) as u8,
+            aff2: reg.read(IROUTER::Aff2) as u8,
+            aff3: reg.read(IROUTER::Aff3) as u8,
+        }),
+    }
+}
+
+/// Default number of polls before giving up on GICD_CTLR.RWP
+pub const RWP_TIMEOUT: u32 = 10000;
+
 /// Access context for CTLR register operations
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SecurityState {
@@ -83,7 +116,7 @@ register_structs! {
         (0x0f80 => pub INMIR: [ReadWrite<u32>; 0x20]),
         (0x1000 => _rsv9: [u32; 5184]),
         /// Interrupt Routing Registers.
-        (0x6100 => pub IROUTER: [ReadWrite<u64>; 987]),
+        (0x6100 => pub IROUTER: [ReadWrite<u64, IROUTER::Register>; 987]),
         (0x7FD8 => _rsv10: [u32; 2]),
         (0x7FE0 => @END),
     }
@@ -152,8 +185,13 @@ impl DistributorReg {
         }
     }
 
+    /// Determine the security configuration of the GIC and of the current access
+    ///
+    /// GICD_TYPER.SecurityExtn is checked first: a GIC without two security
+    /// states may hardwire GICD_CTLR.DS, and the GICD_NSACR probe is only
+    /// meaningful when two security states exist, so it is skipped entirely.
     pub fn get_security_state(&self) -> SecurityState {
-        if self.is_single_security_state() || !self.has_security_extensions() {
+        if !self.has_security_extensions() || self.is_single_security_state() {
             SecurityState::Single
         } else {
             // In two security states configuration, use GICD_NSACR access behavior to determine security state
@@ -180,7 +218,7 @@ impl DistributorReg {
     /// If it reads as zero, we're in Non-secure state.
     fn detect_security_state_via_nsacr(&self) -> SecurityState {
         // Only valid in two security states configuration
-        if self.is_single_security_state() {
+        if !self.has_security_extensions() || self.is_single_security_state() {
             return SecurityState::Single;
         }
 
@@ -223,6 +261,11 @@ impl DistributorReg {
         1u32 << (id_bits + 1)
     }
 
+    /// Number of implemented ICFGR registers (16 interrupts per register)
+    pub fn icfgr_count(&self) -> usize {
+        (self.max_spi_num().div_ceil(16) as usize).min(self.ICFGR.len())
+    }
+
     /// Get the number of interrupt lines (SPIs)
     pub fn max_spi_num(&self) -> u32 {
         let it_lines_number = self.TYPER.read(TYPER::ITLinesNumber);
@@ -318,44 +361,49 @@ impl DistributorReg {
         }
     }
 
-    /// Set interrupt priority
-    pub fn set_priority(&self, intid: u32, priority: u8) {
-        if intid >= 32 && (intid as usize) < self.IPRIORITYR.len() {
-            self.IPRIORITYR[intid as usize].set(priority);
-        }
-    }
-
-    /// Get interrupt priority
-    pub fn get_priority(&self, intid: u32) -> u8 {
-        if intid >= 32 && (intid as usize) < self.IPRIORITYR.len() {
-            self.IPRIORITYR[intid as usize].get()
-        } else {
-            0
-        }
-    }
-
     /// Set default priorities for all interrupts
-    pub fn set_default_priorities(&self, max_interrupts: u32) {
+    pub fn set_default_priorities(&self, max_interrupts: u32, priority: u8) {
         let num_priorities = max_interrupts.min(self.IPRIORITYR.len() as u32);
 
-        // Set default priority (0xA0 - middle priority) for all interrupts
         for i in 32..num_priorities {
-            self.write_ipriorityr(i as usize, 0xA0);
+            self.write_ipriorityr(i as usize, priority);
         }
     }
 
-    /// Configure interrupt groups - set all interrupts to Group 1 by default
-    pub fn groups_all_to_1(&self, max_interrupts: u32) {
+    /// Configure interrupt groups - set all interrupts to the same group
+    ///
+    /// GICD_IGRPMODR is RAZ/WI unless accessed from Secure state with two
+    /// security states, so writing it is harmless elsewhere.
+    pub fn groups_all_to(&self, max_interrupts: u32, group: InterruptGroup) {
         let num_regs = max_interrupts.div_ceil(32) as usize;
         let num_regs = num_regs.min(self.IGROUPR.len());
+        let (igroupr, igrpmodr) = group.bits();
+        let fill = |set: bool| if set { u32::MAX } else { 0 };
 
         for i in 0..num_regs {
-            self.write_igroupr(i, u32::MAX);
+            self.write_igroupr(i, fill(igroupr));
+            self.IGRPMODR[i].set(fill(igrpmodr));
         }
     }
 
-    /// Set interrupt group and modifier
-    pub fn set_interrupt_group(&self, intid: u32, group: u32, group_modifier: bool) {
+    /// Get the GICD_IGROUPR and GICD_IGRPMODR bits of an SPI
+    pub fn interrupt_group(&self, intid: u32) -> (bool, bool) {
+        let reg_idx = (intid / 32) as usize;
+        let bit = 1 << (intid % 32);
+        let group = self
+            .IGROUPR
+            .get(reg_idx)
+            .is_some_and(|r| r.get() & bit != 0);
+        let modifier = self
+            .IGRPMODR
+            .get(reg_idx)
+            .is_some_and(|r| r.get() & bit != 0);
+        (group, modifier)
+    }
+
+    /// Set the GICD_IGROUPR and GICD_IGRPMODR bits of an SPI
+    pub fn set_interrupt_group(&self, intid: u32, group: InterruptGroup) {
+        let (group, group_modifier) = group.bits();
         if intid >= 32 {
             // Only SPIs can be controlled via distributor
             let reg_idx = (intid / 32) as usize;
@@ -363,7 +411,7 @@ impl DistributorReg {
 
             if reg_idx < self.IGROUPR.len() {
                 let current = self.IGROUPR[reg_idx].get();
-                if group != 0 {
+                if group {
                     self.IGROUPR[reg_idx].set(current | (1 << bit_idx));
                 } else {
                     self.IGROUPR[reg_idx].set(current & !(1 << bit_idx));
@@ -388,7 +436,7 @@ impl DistributorReg {
         let bit_offset = (int_num % 16) * 2 + 1; // Each interrupt uses 2 bits, we use bit 1 for edge/level
 
         assert!(
-            reg_index < self.ICFGR.len(),
+            reg_index < self.icfgr_count(),
             "Invalid interrupt ID for config: {id:?}"
         );
 
@@ -404,13 +452,17 @@ impl DistributorReg {
     }
 
     /// Configure interrupt configuration for all interrupts
-    pub fn configure_interrupt_config(&self, max_interrupts: u32) {
+    pub fn configure_interrupt_config(&self, max_interrupts: u32, trigger: Trigger) {
         let num_regs = max_interrupts.div_ceil(16) as usize;
-        let num_regs = num_regs.min(self.ICFGR.len());
+        let num_regs = num_regs.min(self.icfgr_count());
 
-        // Configure all interrupts as level-sensitive (0x0) by default
+        // Int_config[1] of every 2-bit field: 0 = level-sensitive, 1 = edge-triggered
+        let val = match trigger {
+            Trigger::Level => 0,
+            Trigger::Edge => 0xAAAA_AAAA,
+        };
         for i in 0..num_regs {
-            self.write_icfgr(i, 0);
+            self.write_icfgr(i, val);
         }
     }
 
@@ -430,53 +482,18 @@ impl DistributorReg {
             return; // Out of range for IROUTER registers
         }
 
-        let mut route_value = 0u64;
-        match aff {
-            Some(Affinity {
-                aff0,
-                aff1,
-                aff2,
-                aff3,
-            }) => {
-                // Set specific affinity routing
-                route_value |= aff0 as u64;
-                route_value |= (aff1 as u64) << 8;
-                route_value |= (aff2 as u64) << 16;
-                route_value |= (aff3 as u64) << 32;
-                // Ensure Interrupt_Routing_Mode is 0 for specific routing
-                route_value &= !(1u64 << 31);
-            }
-            None => {
-                // Set "any participating PE" routing mode
-                route_value |= 1u64 << 31;
-            }
-        }
-        self.IROUTER[router_idx].set(route_value);
+        self.IROUTER[router_idx].set(encode_route(aff).get());
     }
 
     /// Get interrupt routing information
+    ///
+    /// `None` means 1-of-N routing, or that `intid` has no GICD_IROUTER.
     pub fn get_interrupt_route(&self, intid: u32) -> Option<Affinity> {
         if SPI_RANGE.contains(&intid) {
             let router_idx = (intid - 32) as usize;
 
             if router_idx < self.IROUTER.len() {
-                let route_value = self.IROUTER[router_idx].get();
-                let aff0 = (route_value & 0xFF) as u8;
-                let aff1 = ((route_value >> 8) & 0xFF) as u8;
-                let aff2 = ((route_value >> 16) & 0xFF) as u8;
-                let aff3 = ((route_value >> 32) & 0xFF) as u8;
-                let routing_mode = (route_value & (1u64 << 31)) != 0;
-
-                return if routing_mode {
-                    None
-                } else {
-                    Some(Affinity {
-                        aff0,
-                        aff1,
-                        aff2,
-                        aff3,
-                    })
-                };
+                return decode_route(self.IROUTER[router_idx].extract());
             }
         }
         None
@@ -543,14 +560,16 @@ impl DistributorReg {
 
     /// Check if Extended SPI range is supported
     pub fn has_extended_spi(&self) -> bool {
-        // Check if TYPER2.ESPI is implemented and set
-        self.TYPER2.read(TYPER2::NMI) != 0 // Using NMI bit as placeholder since ESPI is not defined yet
+        self.TYPER.is_set(TYPER::ESPI)
     }
 
-    /// Get the Extended SPI range if supported
+    /// Get the number of implemented Extended SPIs (0 if not supported)
     pub fn extended_spi_range(&self) -> u32 {
-        // This would read TYPER2.ESPI_range field when implemented
-        0 // Placeholder return
+        if self.has_extended_spi() {
+            (self.TYPER.read(TYPER::ESPI_range) + 1) * 32
+        } else {
+            0
+        }
     }
 
     /// Check if Message-based SPIs are supported
@@ -577,7 +596,7 @@ impl DistributorReg {
 
     /// Initialize for two security states configuration (from Secure state)
     /// This handles the case where DS=0 and security extensions are present
-    pub fn reset_registers(&self) {
+    pub fn reset_registers(&self, config: &InitConfig) {
         // Get the maximum number of interrupts
         let max_spis = self.max_spi_num();
 
@@ -588,26 +607,31 @@ impl DistributorReg {
         // Disable all interrupts
         self.irq_disable_all(max_spis);
 
-        // Set all interrupts to Group 1 by default
-        self.groups_all_to_1(max_spis);
+        // Set all interrupts to the default group
+        self.groups_all_to(max_spis, config.default_group);
 
         // Set default priorities
-        self.set_default_priorities(max_spis);
+        self.set_default_priorities(max_spis, config.default_priority);
 
-        // Configure all interrupts as level-sensitive
-        self.configure_interrupt_config(max_spis);
+        // Configure the default trigger mode
+        self.configure_interrupt_config(max_spis, config.default_trigger);
 
         self.set_all_routing_to_current(max_spis);
     }
 
     /// Wait for register write pending to clear
-    pub fn wait_for_rwp(&self) -> Result<(), &'static str> {
-        let mut time_out_count = 10000;
+    pub fn wait_for_rwp(&self) -> Result<(), GicError> {
+        self.wait_for_rwp_timeout(RWP_TIMEOUT)
+    }
+
+    /// Wait for register write pending to clear, polling at most `spins` times
+    pub fn wait_for_rwp_timeout(&self, spins: u32) -> Result<(), GicError> {
+        let mut time_out_count = spins.max(1);
         while self.CTLR.is_set(CTLR_BASE::RWP) {
             spin_loop();
             time_out_count -= 1;
             if time_out_count == 0 {
-                return Err("GICv3 Distributor CTLR RWP wait timeout.");
+                return Err(GicError::RwpTimeout);
             }
         }
         barrier::isb(barrier::SY);
@@ -624,6 +648,8 @@ register_bitfields! [
             TwoSecurityStates = 0,
             SingleSecurityState = 1,
         ],
+        /// SGIs have no active state (GICv4.1)
+        nASSGIreq OFFSET(8) NUMBITS(1) [],
         /// Register Write Pending - read only
         RWP OFFSET(31) NUMBITS(1) [],
     ],
@@ -643,6 +669,7 @@ register_bitfields! [
         EnableGrp1 OFFSET(0) NUMBITS(1) [],
         EnableGrp1A OFFSET(1) NUMBITS(1) [],
         ARE_NS OFFSET(4) NUMBITS(1) [],
+        nASSGIreq OFFSET(8) NUMBITS(1) [],
         RWP OFFSET(31) NUMBITS(1) [],
     ],
     /// When in a system that supports only a single Security state
@@ -662,21 +689,27 @@ register_bitfields! [
         ITLinesNumber OFFSET(0) NUMBITS(5) [],
         /// Number of CPU interfaces implemented minus one
         CPUNumber OFFSET(5) NUMBITS(3) [],
+        /// Extended SPI range implemented
+        ESPI OFFSET(8) NUMBITS(1) [],
+        /// Non-maskable interrupts supported
+        NMI OFFSET(9) NUMBITS(1) [],
         /// Indicates whether the GIC implements Security Extensions
         SecurityExtn OFFSET(10) NUMBITS(1) [
             SingleSecurity = 0,
             TwoSecurity = 1,
         ],
-        /// Number of Lockable Shared Peripheral Interrupts
-        LSPI OFFSET(11) NUMBITS(5) [],
+        /// Number of supported LPIs
+        num_LPIs OFFSET(11) NUMBITS(5) [],
         /// Interrupt identifier bits supported
         IDbits OFFSET(19) NUMBITS(5) [],
         /// Affinity 3 supported
         A3V OFFSET(24) NUMBITS(1) [],
         /// No1ofN behavior supported
         No1N OFFSET(25) NUMBITS(1) [],
-        /// Common not Private base supported
-        CommonLPIAff OFFSET(26) NUMBITS(2) [],
+        /// Range Selector Support
+        RSS OFFSET(26) NUMBITS(1) [],
+        /// Maximum extended SPI INTID
+        ESPI_range OFFSET(27) NUMBITS(5) [],
         /// Message based SPIs supported
         MBIS OFFSET(16) NUMBITS(1) [],
         /// Low Power Interrupt supported
@@ -687,23 +720,23 @@ register_bitfields! [
 
     /// Type Modifier Register
     pub TYPER2 [
-        /// Virtual LPIs supported
-        VIL OFFSET(0) NUMBITS(1) [],
-        /// Virtual command queue interface supported
-        VID OFFSET(1) NUMBITS(5) [],
-        /// NMI support
-        NMI OFFSET(6) NUMBITS(1) [],
+        /// Number of vPE ID bits supported minus one (when VIL is set)
+        VID OFFSET(0) NUMBITS(5) [],
+        /// VID field is valid
+        VIL OFFSET(7) NUMBITS(1) [],
+        /// SGIs can be configured without active state
+        nASSGIcap OFFSET(8) NUMBITS(1) [],
     ],
 
     /// Status Register
     pub STATUSR [
-        /// Register Write Pending
+        /// Read of reserved register detected
         RRD OFFSET(0) NUMBITS(1) [],
-        /// Write register in progress
+        /// Write to reserved register detected
         WRD OFFSET(1) NUMBITS(1) [],
-        /// Register write request failed
+        /// Read of write-only register detected
         RWOD OFFSET(2) NUMBITS(1) [],
-        /// Wake-up request denied
+        /// Write to read-only register detected
         WROD OFFSET(3) NUMBITS(1) [],
     ],
 
@@ -13,7 +13,11 @@ use core::{hint::spin_loop, ops::Index, ptr::NonNull};
 
 use tock_registers::{interfaces::*, register_bitfields, register_structs, registers::*};
 
-use crate::{IntId, define::Trigger, v3::Affinity};
+use crate::{
+    GicError, IntId,
+    define::{EPPI_RANGE, Trigger},
+    v3::{Affinity, InterruptGroup},
+};
 
 pub type RDv3Slice = RedistributorSlice<RedistributorV3>;
 #[allow(unused)]
@@ -45,29 +49,43 @@ impl RedistributorItem for RedistributorV4 {
         &self.lpi
     }
 }
+/// Size of the RD_base and SGI_base frames of a redistributor
+pub const GICR_FRAME_STRIDE_V3: usize = 0x20000;
+/// Size of a redistributor with the additional VLPI_base and reserved frames
+pub const GICR_FRAME_STRIDE_V4: usize = 0x40000;
+
 pub struct RedistributorSlice<T: RedistributorItem> {
     ptr: NonNull<T>,
+    /// Mapped length in bytes, `None` if unknown
+    len: Option<usize>,
 }
 
 impl<T: RedistributorItem> RedistributorSlice<T> {
-    pub fn new(ptr: NonNull<u8>) -> Self {
-        Self { ptr: ptr.cast() }
+    pub fn new(ptr: NonNull<u8>, len: Option<usize>) -> Self {
+        Self {
+            ptr: ptr.cast(),
+            len,
+        }
     }
 
     pub fn iter(&self) -> RedistributorIter<T> {
-        RedistributorIter::new(self.ptr)
+        RedistributorIter::new(self.ptr, self.len)
     }
 }
 
+/// Walks the redistributors up to the one with GICR_TYPER.Last set, or until
+/// the mapped length is used up.
 pub struct RedistributorIter<T: RedistributorItem> {
     ptr: NonNull<T>,
+    remaining: Option<usize>,
     is_last: bool,
 }
 
 impl<T: RedistributorItem> RedistributorIter<T> {
-    pub fn new(p: NonNull<T>) -> Self {
+    pub fn new(p: NonNull<T>, len: Option<usize>) -> Self {
         Self {
             ptr: p,
+            remaining: len,
             is_last: false,
         }
     }
@@ -80,6 +98,9 @@ impl<T: RedistributorItem> Iterator for RedistributorIter<T> {
         if self.is_last {
             return None;
         }
+        if self.remaining.is_some_and(|r| r < size_of::<T>()) {
+            return None;
+        }
         unsafe {
             let ptr = self.ptr;
             let rd = ptr.as_ref();
@@ -87,7 +108,10 @@ impl<T: RedistributorItem> Iterator for RedistributorIter<T> {
             if lpi.TYPER.read(TYPER::Last) > 0 {
                 self.is_last = true;
             }
-            self.ptr = self.ptr.add(1);
+            // GICv4 redistributors with VLPI support have two more frames
+            let stride = lpi.frame_stride();
+            self.remaining = self.remaining.map(|r| r.saturating_sub(stride));
+            self.ptr = self.ptr.byte_add(stride);
             Some(ptr)
         }
     }
@@ -120,6 +144,9 @@ register_structs! {
         (0x0018 => pub MPAMIDR: ReadOnly<u32>),
         (0x001C => pub PARTIDR: ReadWrite<u32>),
         (0x0020 => _rsv0),
+        /// Power Register, implementation defined (GIC-600)
+        (0x0024 => pub PWRR: ReadWrite<u32, PWRR::Register>),
+        (0x0028 => _rsv0a),
         (0x0040 => pub SETLPIR: WriteOnly<u64>),
         (0x0048 => pub CLRLPIR: WriteOnly<u64>),
         (0x0050 => _rsv1),
@@ -154,6 +181,17 @@ register_bitfields! [
         /// Architecture revision
         ArchRev OFFSET(4) NUMBITS(4) [],
     ],
+    /// GIC-600 Power Register
+    PWRR [
+        /// Redistributor power down request
+        RDPD OFFSET(0) NUMBITS(1) [],
+        /// Apply the request to the whole redistributor group
+        RDAG OFFSET(1) NUMBITS(1) [],
+        /// Redistributor group power down requested
+        RDGPD OFFSET(2) NUMBITS(1) [],
+        /// Redistributor group powered off
+        RDGPO OFFSET(3) NUMBITS(1) [],
+    ],
 ];
 
 register_bitfields! [
@@ -187,25 +225,95 @@ register_bitfields! [
     ],
 ];
 
+/// Polls of GICR_WAKER.ChildrenAsleep before a wake/sleep handshake times out
+const WAKER_MAX_RETRIES: u32 = 100000;
+
+/// GICR_IIDR product ID of GIC-600
+const GIC600_PRODUCT_ID: u8 = 0x02;
+
 impl LPI {
+    /// Check whether this redistributor belongs to a GIC-600 (GICR_IIDR)
+    pub fn is_gic600(&self) -> bool {
+        let iidr = crate::Iidr::new(self.IIDR.get(), 3);
+        iidr.implementer() == crate::IMPLEMENTER_ARM && iidr.product_id() == GIC600_PRODUCT_ID
+    }
+
+    /// Power up a GIC-600 redistributor through GICR_PWRR
+    ///
+    /// GIC-600 keeps GICR_WAKER from completing until the redistributor is
+    /// powered, so this must run before [`Self::wake`]. The sequence waits
+    /// for the redistributor group to leave any power transition, requests
+    /// power on, and repeats until GICR_PWRR.RDPD reads as clear.
+    pub fn power_on(&self) -> Result<(), GicError> {
+        let mut retries = 0;
+        loop {
+            // Wait until the group is not transitioning (RDGPD == RDGPO)
+            while self.PWRR.is_set(PWRR::RDGPD) != self.PWRR.is_set(PWRR::RDGPO) {
+                if retries > WAKER_MAX_RETRIES {
+                    return Err(GicError::Timeout);
+                }
+                spin_loop();
+                retries += 1;
+            }
+
+            self.PWRR.write(PWRR::RDPD::CLEAR);
+            if !self.PWRR.is_set(PWRR::RDPD) {
+                return Ok(());
+            }
+            if retries > WAKER_MAX_RETRIES {
+                return Err(GicError::Timeout);
+            }
+            retries += 1;
+        }
+    }
+
     /// Wake up the redistributor
-    pub fn wake(&self) -> Result<(), &'static str> {
+    pub fn wake(&self) -> Result<(), GicError> {
         self.WAKER.write(WAKER::ProcessorSleep::CLEAR);
 
+        let mut retries = 0;
         while self.WAKER.is_set(WAKER::ChildrenAsleep) {
+            if retries > WAKER_MAX_RETRIES {
+                return Err(GicError::Timeout);
+            }
             spin_loop();
+            retries += 1;
         }
 
         self.wait_for_rwp()
     }
 
-    pub fn wait_for_rwp(&self) -> Result<(), &'static str> {
+    /// Check whether the redistributor is awake (GICR_WAKER.ProcessorSleep
+    /// and ChildrenAsleep both clear)
+    pub fn is_awake(&self) -> bool {
+        !self.WAKER.is_set(WAKER::ProcessorSleep) && !self.WAKER.is_set(WAKER::ChildrenAsleep)
+    }
+
+    /// Put the redistributor to sleep
+    ///
+    /// Waits for GICR_WAKER.ChildrenAsleep, i.e. for the interface to the
+    /// CPU to be quiescent.
+    pub fn sleep(&self) -> Result<(), GicError> {
+        self.WAKER.modify(WAKER::ProcessorSleep::SET);
+
+        let mut retries = 0;
+        while !self.WAKER.is_set(WAKER::ChildrenAsleep) {
+            if retries > WAKER_MAX_RETRIES {
+                return Err(GicError::Timeout);
+            }
+            spin_loop();
+            retries += 1;
+        }
+        Ok(())
+    }
+
+    pub fn wait_for_rwp(&self) -> Result<(), GicError> {
         const MAX_RETRIES: u32 = 1000;
         let mut retries = 0;
 
         while self.CTLR.is_set(RCtrl::RWP) {
             if retries > MAX_RETRIES {
-                return Err("Timeout waiting for register write to complete");
+                return Err(GicError::RwpTimeout);
             }
             core::hint::spin_loop();
             retries += 1;
@@ -227,6 +335,41 @@ impl LPI {
         }
     }
 
+    /// Set whether this PE participates in 1-of-N SPI distribution
+    ///
+    /// Clearing participation sets GICR_CTLR.DPG0/DPG1NS/DPG1S; bits not
+    /// accessible from the current security state are RAZ/WI.
+    pub fn set_participating(&self, participate: bool) -> Result<(), GicError> {
+        if participate {
+            self.CTLR
+                .modify(RCtrl::DPG0::CLEAR + RCtrl::DPG1NS::CLEAR + RCtrl::DPG1S::CLEAR);
+        } else {
+            self.CTLR
+                .modify(RCtrl::DPG0::SET + RCtrl::DPG1NS::SET + RCtrl::DPG1S::SET);
+        }
+        self.wait_for_rwp()
+    }
+
+    /// Check whether this PE takes part in 1-of-N distribution of an
+    /// interrupt of `group`
+    pub fn is_participating(&self, group: InterruptGroup) -> bool {
+        let dpg = match group {
+            InterruptGroup::Group0 => RCtrl::DPG0,
+            InterruptGroup::Group1NonSecure => RCtrl::DPG1NS,
+            InterruptGroup::Group1Secure => RCtrl::DPG1S,
+        };
+        !self.CTLR.is_set(dpg)
+    }
+
+    /// Distance to the next redistributor, from GICR_TYPER.VLPIS
+    pub fn frame_stride(&self) -> usize {
+        if self.TYPER.is_set(TYPER::VLPIS) {
+            GICR_FRAME_STRIDE_V4
+        } else {
+            GICR_FRAME_STRIDE_V3
+        }
+    }
+
     /// Check if LPI is enabled
     pub fn is_lpi_enabled(&self) -> bool {
         self.CTLR.is_set(RCtrl::EnableLPIs)
@@ -278,6 +421,20 @@ impl LPI {
     pub fn supports_virtual_lpi(&self) -> bool {
         self.TYPER.is_set(TYPER::VLPIS)
     }
+
+    /// Get the number of implemented Extended PPIs (0, 32 or 64)
+    pub fn extended_ppi_count(&self) -> u32 {
+        match self.TYPER.read(TYPER::PPInum) {
+            1 => 32,
+            2 => 64,
+            _ => 0,
+        }
+    }
+
+    /// Get the GIC architecture revision (3 = GICv3, 4 = GICv4)
+    pub fn arch_rev(&self) -> u8 {
+        self.PIDR2.read(PIDR2::ArchRev) as u8
+    }
 }
 
 register_structs! {
@@ -360,66 +517,90 @@ impl SGI {
         }
     }
 
+    /// Register and bit of `intid` in a GICR_<reg>0 / GICR_<reg><n>E pair.
+    ///
+    /// SGIs and PPIs use `reg0`, Extended PPIs the `ext` register of their
+    /// 32-interrupt bank.
+    fn bank<'a>(
+        intid: IntId,
+        reg0: &'a ReadWrite<u32>,
+        ext: &'a [ReadWrite<u32>],
+    ) -> (&'a ReadWrite<u32>, u32) {
+        let int_id = intid.to_u32();
+        if intid.is_eppi() {
+            let offset = int_id - EPPI_RANGE.start;
+            (&ext[(offset / 32) as usize], 1 << (offset % 32))
+        } else {
+            (reg0, 1 << (int_id % 32))
+        }
+    }
+
     /// Set interrupt enable state
     pub fn set_enable_interrupt(&self, irq: IntId, enable: bool) {
-        let int_id: u32 = irq.into();
-        let bit = 1 << (int_id % 32);
-        if enable {
-            self.ISENABLER0.set(bit);
+        let (reg, bit) = if enable {
+            Self::bank(irq, &self.ISENABLER0, &self.ISENABLER_E)
         } else {
-            self.ICENABLER0.set(bit);
-        }
+            Self::bank(irq, &self.ICENABLER0, &self.ICENABLER_E)
+        };
+        reg.set(bit);
+    }
+
+    /// Enable or disable an Extended PPI through GICR_ISENABLER<n>E/ICENABLER<n>E
+    pub fn set_extended_enable(&self, irq: IntId, enable: bool) {
+        self.set_enable_interrupt(irq, enable);
     }
 
     pub fn is_interrupt_enabled(&self, irq: IntId) -> bool {
-        let int_id: u32 = irq.into();
-        let bit = 1 << (int_id % 32);
-        (self.ISENABLER0.get() & bit) != 0
+        let (reg, bit) = Self::bank(irq, &self.ISENABLER0, &self.ISENABLER_E);
+        (reg.get() & bit) != 0
+    }
+
+    /// GICR_IPRIORITYR<n> or GICR_IPRIORITYR<n>E byte of `intid`
+    fn priority_reg(&self, intid: IntId) -> &ReadWrite<u8> {
+        let int_id = intid.to_u32();
+        if intid.is_eppi() {
+            &self.IPRIORITYR_E[(int_id - EPPI_RANGE.start) as usize]
+        } else {
+            &self.IPRIORITYR[int_id as usize]
+        }
     }
 
     /// Set interrupt priority
     pub fn set_priority(&self, intid: IntId, priority: u8) {
-        self.IPRIORITYR[u32::from(intid) as usize].set(priority)
+        self.priority_reg(intid).set(priority)
     }
 
     pub fn get_priority(&self, intid: IntId) -> u8 {
-        self.IPRIORITYR[u32::from(intid) as usize].get()
+        self.priority_reg(intid).get()
     }
 
-    /// Set interrupt configuration (edge/level triggered)
-    pub fn set_cfgr(&self, intid: IntId, trigger: Trigger) {
+    /// GICR_ICFGR<n> or GICR_ICFGR<n>E register and edge bit of `intid`
+    ///
+    /// Each interrupt uses 2 bits, bit 1 selects edge or level.
+    fn cfgr_bit(&self, intid: IntId) -> (&ReadWrite<u32>, u32) {
         let int_id = intid.to_u32();
-        let bit_offset = (int_id % 16) * 2 + 1; // Each interrupt uses 2 bits, we use bit 1 for edge/level
-        let clean = !(1u32 << bit_offset);
-        let bit: u32 = match trigger {
-            Trigger::Edge => 1,
-            Trigger::Level => 0,
-        } << bit_offset;
-
-        if intid.is_sgi() {
-            let mut mask = self.ICFGR[0].get();
-            mask &= clean;
-            mask |= bit;
-            self.ICFGR[0].set(mask);
+        let (reg, n) = if intid.is_eppi() {
+            let offset = int_id - EPPI_RANGE.start;
+            (2 + (offset / 16) as usize, offset % 16)
         } else {
-            let mut mask = self.ICFGR[1].get();
-            mask &= clean;
-            mask |= bit;
-            self.ICFGR[1].set(mask);
-        }
+            ((int_id / 16) as usize, int_id % 16)
+        };
+        (&self.ICFGR[reg], 1 << (n * 2 + 1))
+    }
+
+    /// Set interrupt configuration (edge/level triggered)
+    pub fn set_cfgr(&self, intid: IntId, trigger: Trigger) {
+        let (reg, bit) = self.cfgr_bit(intid);
+        let mask = reg.get() & !bit;
+        reg.set(match trigger {
+            Trigger::Edge => mask | bit,
+            Trigger::Level => mask,
+        });
     }
 
     pub fn get_cfgr(&self, intid: IntId) -> Trigger {
-        let int_id = intid.to_u32();
-        let bit_offset = (int_id % 16) * 2 + 1; // Each interrupt uses 2 bits, we use bit 1 for edge/level
-        let mask = 1u32 << bit_offset;
-        if intid.is_sgi() {
-            if self.ICFGR[0].get() & mask != 0 {
-                Trigger::Edge
-            } else {
-                Trigger::Level
-            }
-        } else if self.ICFGR[1].get() & mask != 0 {
+        let (reg, bit) = self.cfgr_bit(intid);
+        if reg.get() & bit != 0 {
             Trigger::Edge
         } else {
             Trigger::Level
@@ -428,65 +609,63 @@ impl SGI {
 
     /// Set interrupt pending state
     pub fn set_pending(&self, intid: IntId, pending: bool) {
-        let int_id: u32 = intid.into();
-        let bit = 1 << (int_id % 32);
-        if pending {
-            self.ISPENDR0.set(bit);
+        let (reg, bit) = if pending {
+            Self::bank(intid, &self.ISPENDR0, &self.ISPENDR_E)
         } else {
-            self.ICPENDR0.set(bit);
-        }
+            Self::bank(intid, &self.ICPENDR0, &self.ICPENDR_E)
+        };
+        reg.set(bit);
     }
 
     pub fn is_pending(&self, intid: IntId) -> bool {
-        let int_id: u32 = intid.into();
-        let bit = 1 << (int_id % 32);
-        (self.ISPENDR0.get() & bit) != 0
+        let (reg, bit) = Self::bank(intid, &self.ISPENDR0, &self.ISPENDR_E);
+        (reg.get() & bit) != 0
     }
 
     /// Set interrupt active state
     pub fn set_active(&self, intid: IntId, active: bool) {
-        let int_id: u32 = intid.into();
-        let bit = 1 << (int_id % 32);
-        if active {
-            self.ISACTIVER0.set(bit);
+        let (reg, bit) = if active {
+            Self::bank(intid, &self.ISACTIVER0, &self.ISACTIVER_E)
         } else {
-            self.ICACTIVER0.set(bit);
-        }
+            Self::bank(intid, &self.ICACTIVER0, &self.ICACTIVER_E)
+        };
+        reg.set(bit);
     }
 
     pub fn is_active(&self, intid: IntId) -> bool {
-        let int_id: u32 = intid.into();
-        let bit = 1 << (int_id % 32);
-        (self.ISACTIVER0.get() & bit) != 0
+        let (reg, bit) = Self::bank(intid, &self.ISACTIVER0, &self.ISACTIVER_E);
+        (reg.get() & bit) != 0
     }
 
     /// Set interrupt group
     pub fn set_group(&self, intid: IntId, group1: bool) {
-        let int_id: u32 = intid.into();
-        let bit = 1 << (int_id % 32);
+        let (reg, bit) = Self::bank(intid, &self.IGROUPR0, &self.IGROUPR_E);
         if group1 {
-            self.IGROUPR0.set(self.IGROUPR0.get() | bit);
+            reg.set(reg.get() | bit);
         } else {
-            self.IGROUPR0.set(self.IGROUPR0.get() & !bit);
+            reg.set(reg.get() & !bit);
         }
     }
 
     pub fn is_group1(&self, intid: IntId) -> bool {
-        let int_id: u32 = intid.into();
-        let bit = 1 << (int_id % 32);
-        (self.IGROUPR0.get() & bit) != 0
+        let (reg, bit) = Self::bank(intid, &self.IGROUPR0, &self.IGROUPR_E);
+        (reg.get() & bit) != 0
     }
 
     /// Set interrupt group modifier
     pub fn set_group_modifier(&self, intid: IntId, modifier: bool) {
-        let int_id: u32 = intid.into();
-        let bit = 1 << (int_id % 32);
+        let (reg, bit) = Self::bank(intid, &self.IGRPMODR0, &self.IGRPMODR_E);
         if modifier {
-            self.IGRPMODR0.set(self.IGRPMODR0.get() | bit);
+            reg.set(reg.get() | bit);
         } else {
-            self.IGRPMODR0.set(self.IGRPMODR0.get() & !bit);
+            reg.set(reg.get() & !bit);
         }
     }
+
+    pub fn is_group_modifier(&self, intid: IntId) -> bool {
+        let (reg, bit) = Self::bank(intid, &self.IGRPMODR0, &self.IGRPMODR_E);
+        (reg.get() & bit) != 0
+    }
 }
 
 register_bitfields! [
@@ -506,6 +685,8 @@ register_bitfields! [
         CommonLPIAff OFFSET(24) NUMBITS(2) [],
         /// Processor Number
         ProcessorNumber OFFSET(8) NUMBITS(16) [],
+        /// Number of Extended PPIs supported
+        PPInum OFFSET(27) NUMBITS(5) [],
         /// Affinity value
         Affinity OFFSET(32) NUMBITS(32) [],
     ],
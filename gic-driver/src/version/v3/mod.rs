@@ -1,20 +1,32 @@
-use core::ptr::NonNull;
+use core::{cell::Cell, fmt, ops::Range, ptr::NonNull};
 
 use aarch64_cpu::{
     asm::barrier,
     registers::{CurrentEL, MPIDR_EL1},
 };
 use log::*;
+use tock_registers::registers::ReadWrite;
 pub use tock_registers::{LocalRegisterCopy, interfaces::*};
 
 mod gicd;
 mod gicr;
+#[cfg(target_arch = "aarch64")]
+mod vcpu;
 
-pub use crate::{IntId, VirtAddr, define::Trigger, sys_reg::*};
+use crate::define::{EPPI_RANGE, ESPI_RANGE, IntClass};
+pub use crate::{GicError, Iidr, IntId, VirtAddr, define::Trigger, sys_reg::*};
 
-use crate::version::{IrqVecReadable, IrqVecWriteable};
+use crate::version::{IrqVecReadable, IrqVecWriteable, iter_irq_bits};
+pub use gicd::SecurityState;
 use gicd::*;
 use gicr::*;
+#[cfg(target_arch = "aarch64")]
+pub use vcpu::*;
+
+#[cfg(test)]
+pub(crate) use gicd::{decode_route, encode_route};
+#[cfg(test)]
+pub(crate) use gicr::{LPI, RedistributorV3, RedistributorV4, SGI};
 
 /// SGI target specification for GICv3.
 ///
@@ -54,36 +66,59 @@ pub struct TargetList {
     aff2: u8,
     /// Affinity level 1
     aff1: u8,
-    /// Target list bitmap (16-bit) identifying CPUs at affinity level 0
-    target_list: u16,
+    /// Target list bitmaps identifying CPUs at affinity level 0, one 16-bit
+    /// list per range selector value (Aff0 = RS * 16 + bit)
+    target_list: [u16; 16],
 }
 
 impl TargetList {
     /// Create a new TargetList with a specific CPU target list. list is Cpu interface IDs.
+    ///
+    /// Affinity level 0 values of 16 and above need range selector support,
+    /// see [`CpuCaps::range_selector`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the targets differ above affinity level 0, see [`Self::try_new`].
     pub fn new(list: impl AsRef<[Affinity]>) -> Self {
+        match Self::try_new(list) {
+            Ok(list) => list,
+            Err(e) => panic!("Invalid SGI target list: {e}"),
+        }
+    }
+
+    /// Create a TargetList, rejecting targets that cannot share one SGI.
+    ///
+    /// ICC_SGI1R_EL1 carries a single `Aff3.Aff2.Aff1` value, so all targets
+    /// must agree on those levels. Whether a non-zero `aff3` is usable is
+    /// only known to the CPU interface and checked by [`send_sgi`]
+    /// ([`GicError::Affinity3NotSupported`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::MixedTargetAffinity`] with the first target whose
+    /// upper affinity levels differ from the first entry.
+    pub fn try_new(list: impl AsRef<[Affinity]>) -> Result<Self, GicError> {
         let mut aff3 = 0;
         let mut aff2 = 0;
         let mut aff1 = 0;
-        let mut raw = 0;
+        let mut raw = [0u16; 16];
         for (i, aff) in list.as_ref().iter().enumerate() {
             if i == 0 {
                 aff3 = aff.aff3;
                 aff2 = aff.aff2;
                 aff1 = aff.aff1;
-            } else {
-                assert!(
-                    aff.aff3 == aff3 && aff.aff2 == aff2 && aff.aff1 == aff1,
-                    "All targets must have the same affinity levels except for level 0"
-                );
+            } else if aff.aff3 != aff3 || aff.aff2 != aff2 || aff.aff1 != aff1 {
+                return Err(GicError::MixedTargetAffinity(aff.affinity()));
             }
-            raw |= 1 << aff.aff0; // Set bit for each target CPU
+            raw[(aff.aff0 / 16) as usize] |= 1 << (aff.aff0 % 16); // Set bit for each target CPU
         }
-        Self {
+        Ok(Self {
             aff3,
             aff2,
             aff1,
             target_list: raw,
-        }
+        })
     }
 
     pub fn add(&mut self, affinity: Affinity) {
@@ -91,12 +126,12 @@ impl TargetList {
             affinity.aff3 == self.aff3 && affinity.aff2 == self.aff2 && affinity.aff1 == self.aff1,
             "All targets must have the same affinity levels except for level 0"
         );
-        self.target_list |= 1 << affinity.aff0; // Set bit for the target CPU
+        self.target_list[(affinity.aff0 / 16) as usize] |= 1 << (affinity.aff0 % 16); // Set bit for the target CPU
     }
 
     pub fn affinity_list(&self) -> impl Iterator<Item = Affinity> {
-        (0..16)
-            .filter(move |i| (self.target_list & (1 << i)) != 0)
+        (0..256usize)
+            .filter(move |i| (self.target_list[i / 16] & (1 << (i % 16))) != 0)
             .map(move |i| Affinity {
                 aff3: self.aff3,
                 aff2: self.aff2,
@@ -155,6 +190,16 @@ impl Affinity {
             | ((self.aff3 as u32) << 24)
     }
 
+    /// Inverse of [`Self::affinity`], e.g. for GICR_TYPER.Affinity.
+    pub(crate) fn from_affinity(val: u32) -> Self {
+        Self {
+            aff0: val as u8,
+            aff1: (val >> 8) as u8,
+            aff2: (val >> 16) as u8,
+            aff3: (val >> 24) as u8,
+        }
+    }
+
     /// Create an `Affinity` from an MPIDR register value.
     ///
     /// Extracts the affinity levels from the Multiprocessor Affinity Register
@@ -211,6 +256,9 @@ impl Affinity {
     }
 }
 
+/// Size of the GICD register frame
+pub const GICD_FRAME_SIZE: usize = 0x10000;
+
 /// GICv3 driver implementation.
 ///
 /// This structure provides the main interface for controlling a GICv3 interrupt controller.
@@ -240,7 +288,7 @@ impl Affinity {
 /// let gicr_addr = VirtAddr::new(0x0806_0000);
 ///
 /// let mut gic = unsafe { Gic::new(gicd_addr, gicr_addr) };
-/// gic.init();
+/// gic.init().unwrap();
 ///
 /// // Initialize CPU interface for current CPU
 /// let mut cpu_if = gic.cpu_interface();
@@ -249,11 +297,50 @@ impl Affinity {
 pub struct Gic {
     gicd: VirtAddr,
     gicr: VirtAddr,
+    /// Mapped length of the redistributor region, if known
+    gicr_size: Option<usize>,
     security_state: SecurityState,
+    /// `security_state` was given by the caller and must not be probed
+    fixed_security_state: bool,
 }
 
 unsafe impl Send for Gic {}
 
+impl fmt::Debug for Gic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let iidr = self.gicd().IIDR.extract();
+        f.debug_struct("Gic")
+            .field("gicd", &self.gicd)
+            .field("gicr", &self.gicr)
+            .field("security_state", &self.security_state)
+            .field("arch_rev", &self.arch_rev())
+            .field(
+                "implementer",
+                &format_args!("{:#x}", iidr.read(IIDR::Implementer)),
+            )
+            .field("revision", &iidr.read(IIDR::Revision))
+            .field("max_intid", &self.max_intid())
+            .finish()
+    }
+}
+
+impl fmt::Display for Gic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let iidr = self.gicd().IIDR.extract();
+        write!(
+            f,
+            "GICv{} at {:#x}: implementer {:#x} r{}p{}, {} SPIs, security {:?}",
+            self.arch_rev(),
+            usize::from(self.gicd),
+            iidr.read(IIDR::Implementer),
+            iidr.read(IIDR::Variant),
+            iidr.read(IIDR::Revision),
+            self.gicd().max_spi_num().min(SPECIAL_RANGE_START) - 32,
+            self.security_state,
+        )
+    }
+}
+
 impl Gic {
     /// Create a new GICv3 driver instance.
     ///
@@ -284,7 +371,87 @@ impl Gic {
         Self {
             gicd,
             gicr,
+            gicr_size: None,
+            security_state: SecurityState::Single,
+            fixed_security_state: false,
+        }
+    }
+
+    /// Create a GICv3 driver instance that knows the mapped length of the
+    /// redistributor region.
+    ///
+    /// Walking the redistributors stops at the end of the `gicr_size` bytes
+    /// instead of reading past the mapping when no frame has GICR_TYPER.Last
+    /// set, and [`Self::init`] reports such a mapping as
+    /// [`GicError::RedistributorRegionTooSmall`].
+    ///
+    /// # Safety
+    ///
+    /// Same requirements as [`Self::new`], for the first `gicr_size` bytes
+    /// at `gicr`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use arm_gic_driver::{VirtAddr, v3::Gic};
+    ///
+    /// let mut gic = unsafe {
+    ///     Gic::new_with_gicr_size(VirtAddr::new(0x0800_0000), VirtAddr::new(0x080A_0000), 0xF6_0000)
+    /// };
+    /// gic.init().unwrap();
+    /// ```
+    pub const unsafe fn new_with_gicr_size(
+        gicd: VirtAddr,
+        gicr: VirtAddr,
+        gicr_size: usize,
+    ) -> Self {
+        Self {
+            gicd,
+            gicr,
+            gicr_size: Some(gicr_size),
+            security_state: SecurityState::Single,
+            fixed_security_state: false,
+        }
+    }
+
+    /// Create a GICv3 driver instance for a single security state system.
+    ///
+    /// Unlike [`Self::new`], [`Self::init`] and [`Self::reinit`] keep
+    /// [`SecurityState::Single`] instead of detecting the security state, so
+    /// GICD_NSACR is never touched. Use this when the configuration is known
+    /// up front, e.g. in a guest where a hypervisor traps GICD_NSACR.
+    ///
+    /// # Safety
+    ///
+    /// Same requirements as [`Self::new`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use arm_gic_driver::{VirtAddr, v3::Gic};
+    ///
+    /// let mut gic = unsafe {
+    ///     Gic::new_single_security(VirtAddr::new(0x0800_0000), VirtAddr::new(0x080A_0000))
+    /// };
+    /// gic.init().unwrap();
+    /// ```
+    pub const unsafe fn new_single_security(gicd: VirtAddr, gicr: VirtAddr) -> Self {
+        Self {
+            gicd,
+            gicr,
+            gicr_size: None,
             security_state: SecurityState::Single,
+            fixed_security_state: true,
+        }
+    }
+
+    /// Security state to run with, probed from the distributor unless it was
+    /// fixed at construction.
+    fn detect_security_state(&self) -> SecurityState {
+        if self.fixed_security_state {
+            self.security_state
+        } else {
+            self.gicd().get_security_state()
         }
     }
 
@@ -299,6 +466,59 @@ impl Gic {
     pub fn gicd_addr(&self) -> VirtAddr {
         self.gicd
     }
+
+    /// Base of the distributor register frame, for registers the typed API
+    /// does not model.
+    pub fn gicd_ptr(&self) -> NonNull<u8> {
+        unsafe { NonNull::new_unchecked(self.gicd.as_ptr()) }
+    }
+
+    /// Read the 32-bit distributor register at byte `offset`.
+    ///
+    /// Escape hatch for implementation-defined registers, e.g. from an
+    /// erratum workaround. The access is a single volatile load.
+    ///
+    /// # Safety
+    ///
+    /// Reads of some registers have side effects. The caller must make sure
+    /// the register at `offset` exists on this implementation and that
+    /// reading it does not interfere with the driver's own use of the GIC.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is not 4-byte aligned or the access does not fit
+    /// the 64 KiB frame ([`GICD_FRAME_SIZE`]).
+    pub unsafe fn read_gicd_u32(&self, offset: usize) -> u32 {
+        let ptr = self.gicd_reg_ptr(offset);
+        unsafe { ptr.read_volatile() }
+    }
+
+    /// Write the 32-bit distributor register at byte `offset`.
+    ///
+    /// Counterpart of [`Self::read_gicd_u32`]; the access is a single
+    /// volatile store.
+    ///
+    /// # Safety
+    ///
+    /// The caller must make sure the register at `offset` exists on this
+    /// implementation and that writing `val` leaves the GIC in a state the
+    /// driver can still operate on.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`Self::read_gicd_u32`].
+    pub unsafe fn write_gicd_u32(&self, offset: usize, val: u32) {
+        let ptr = self.gicd_reg_ptr(offset);
+        unsafe { ptr.write_volatile(val) }
+    }
+
+    fn gicd_reg_ptr(&self, offset: usize) -> *mut u32 {
+        assert!(
+            offset.is_multiple_of(4) && offset + 4 <= GICD_FRAME_SIZE,
+            "Invalid GICD register offset: {offset:#x}"
+        );
+        unsafe { self.gicd.as_ptr::<u8>().add(offset).cast() }
+    }
     /// Initialize the GICv3 Distributor according to ARM GIC Architecture Specification v3/v4
     ///
     /// This function implements the initialization sequence described in section 12.9.4
@@ -320,9 +540,15 @@ impl Gic {
     /// 5. Enable affinity routing
     /// 6. Enable appropriate interrupt groups
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// Panics if register write operations timeout, indicating hardware issues.
+    /// Returns [`GicError::RedistributorRegionTooSmall`] if the mapped
+    /// redistributor region ends early, or [`GicError::RwpTimeout`] if a
+    /// distributor register write does not complete, indicating hardware
+    /// issues. [`Self::init_or_panic`] panics instead.
+    ///
+    /// SPIs are reset to the [`InitConfig::default`] priority, group and
+    /// trigger; use [`Self::init_with_config`] for other defaults.
     ///
     /// # Examples
     ///
@@ -332,30 +558,102 @@ impl Gic {
     /// let mut gic = unsafe {
     ///     Gic::new(VirtAddr::new(0x0800_0000), VirtAddr::new(0x0806_0000))
     /// };
-    /// gic.init(); // Initialize the distributor
+    /// gic.init().unwrap(); // Initialize the distributor
+    /// ```
+    pub fn init(&mut self) -> Result<(), GicError> {
+        self.try_init(&InitParams::default())
+    }
+
+    /// Initialize the distributor like [`Self::init`], panicking on failure.
+    ///
+    /// # Panics
+    ///
+    /// Panics if register write operations timeout, indicating hardware issues.
+    pub fn init_or_panic(&mut self) {
+        if let Err(e) = self.init() {
+            panic!("Failed to initialize GICv3: {}", e);
+        }
+    }
+
+    /// Initialize the distributor like [`Self::init`], with the per-SPI
+    /// defaults taken from `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Self::init`], or
+    /// [`GicError::SecureGroupUnavailable`] if `config` asks for
+    /// [`InterruptGroup::Group1Secure`] outside the Secure state.
+    pub fn init_with_config(&mut self, config: InitConfig) -> Result<(), GicError> {
+        self.try_init(&InitParams {
+            config,
+            ..Default::default()
+        })
+    }
+
+    /// Initialize the distributor, then the CPU interface of the calling CPU.
+    ///
+    /// Runs the [`Self::init`] sequence, acquires [`Self::cpu_interface`] and
+    /// calls [`CpuInterface::init_current_cpu`] on it, returning the ready
+    /// interface. Secondary CPUs still need their own
+    /// [`CpuInterface::init_current_cpu`].
+    ///
+    /// IRQs stay masked in PSTATE.DAIF; the caller unmasks them once its
+    /// handlers are in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Self::init`] and
+    /// [`CpuInterface::init_current_cpu`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use arm_gic_driver::{VirtAddr, v3::Gic};
+    /// # let mut gic = unsafe { Gic::new(VirtAddr::new(0), VirtAddr::new(0)) };
+    /// let cpu = gic.init_and_bring_up_current_cpu().unwrap();
+    /// unsafe { core::arch::asm!("msr daifclr, #2") };
     /// ```
-    pub fn init(&mut self) {
-        // Read current configuration to determine security state
+    pub fn init_and_bring_up_current_cpu(&mut self) -> Result<CpuInterface, GicError> {
+        self.init()?;
+        let mut cpu = self.cpu_interface();
+        cpu.init_current_cpu()?;
+        Ok(cpu)
+    }
 
-        self.security_state = self.gicd().get_security_state();
+    fn try_init(&mut self, params: &InitParams) -> Result<(), GicError> {
+        // Read current configuration to determine security state, unless overridden
+        self.security_state = params
+            .security_state
+            .unwrap_or_else(|| self.detect_security_state());
 
         trace!(
             "Initializing GICv3 Distributor@{:#p}, security state: {:?}...",
             self.gicd.as_ptr::<u8>(),
             self.security_state
         );
+        if params.config.default_group == InterruptGroup::Group1Secure
+            && self.security_state != SecurityState::Secure
+        {
+            return Err(GicError::SecureGroupUnavailable);
+        }
+        self.check_redistributor_region()?;
 
         // 1. Disable all interrupt groups before configuration
         self.disable();
         barrier::isb(barrier::SY);
 
         // Wait for register write to complete
-        if let Err(e) = self.gicd().wait_for_rwp() {
-            panic!("Failed to disable GICv3 during init: {}", e);
-        }
+        self.gicd().wait_for_rwp_timeout(params.rwp_timeout)?;
         trace!("GICv3 Distributor disabled");
 
-        self.gicd().reset_registers();
+        // GICD_IROUTER<n> is RES0 while ARE is clear, so enable affinity
+        // routing, with all groups still disabled, before resetting routes
+        let old = self.gicd().CTLR.get();
+        self.gicd().CTLR.set(old | self.are_bits());
+        barrier::isb(barrier::SY);
+        self.gicd().wait_for_rwp_timeout(params.rwp_timeout)?;
+
+        self.gicd().reset_registers(&params.config);
 
         let ctrl = match self.security_state {
             SecurityState::Secure => {
@@ -380,9 +678,66 @@ impl Gic {
         barrier::isb(barrier::SY);
 
         // Wait for final configuration to complete
-        if let Err(e) = self.gicd().wait_for_rwp() {
-            panic!("Failed to complete GICv3 initialization: {}", e);
+        self.gicd().wait_for_rwp_timeout(params.rwp_timeout)?;
+
+        if let Err(e) = self.check_status() {
+            warn!("GICv3 Distributor reported access errors during init: {e}");
         }
+        Ok(())
+    }
+
+    /// Re-initialize a distributor that may have been left running.
+    ///
+    /// Used when taking over a GIC that was already configured, e.g. after
+    /// kexec. All interrupt groups are disabled and affinity routing is
+    /// cleared before the normal [`Self::init`] sequence runs, which then
+    /// enables affinity routing again before resetting the SPI routes and
+    /// draining pending/active state.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::RwpTimeout`] if disabling the distributor or
+    /// clearing affinity routing does not complete, and otherwise the errors
+    /// of [`Self::init`].
+    pub fn reinit(&mut self) -> Result<(), GicError> {
+        self.security_state = self.detect_security_state();
+
+        self.disable();
+        self.gicd().wait_for_rwp()?;
+
+        // ARE may only be changed while all interrupt groups are disabled
+        let old = self.gicd().CTLR.get();
+        self.gicd().CTLR.set(old & !self.are_bits());
+        barrier::isb(barrier::SY);
+        self.gicd().wait_for_rwp()?;
+        trace!("GICv3 Distributor quiesced, running init");
+
+        self.init()
+    }
+
+    /// Check GICD_STATUSR for detected register access errors.
+    ///
+    /// The error bits are sticky; any that are set are cleared before
+    /// returning. GICD_STATUSR is optional, on implementations without it this
+    /// always returns `Ok(())`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GicStatusError`] describing which accesses were flagged.
+    pub fn check_status(&self) -> Result<(), GicStatusError> {
+        let status = self.gicd().STATUSR.extract();
+        let err = GicStatusError {
+            read_reserved: status.is_set(STATUSR::RRD),
+            write_reserved: status.is_set(STATUSR::WRD),
+            read_write_only: status.is_set(STATUSR::RWOD),
+            write_read_only: status.is_set(STATUSR::WROD),
+        };
+        if status.get() & 0xF == 0 {
+            return Ok(());
+        }
+        // Write-one-to-clear
+        self.gicd().STATUSR.set(status.get() & 0xF);
+        Err(err)
     }
 
     /// Get the maximum interrupt ID supported by this GIC implementation.
@@ -412,24 +767,79 @@ impl Gic {
         let old = self.gicd().CTLR.get();
         let val = match self.security_state {
             SecurityState::Secure => {
-                (CTLR_S::EnableGrp0::CLEAR
-                    + CTLR_S::EnableGrp1S::CLEAR
-                    + CTLR_S::EnableGrp1NS::CLEAR)
+                (CTLR_S::EnableGrp0::SET + CTLR_S::EnableGrp1S::SET + CTLR_S::EnableGrp1NS::SET)
                     .value
             }
             SecurityState::NonSecure => {
-                (CTLR_NS::EnableGrp1::CLEAR + CTLR_NS::EnableGrp1A::CLEAR).value
-            }
-            SecurityState::Single => {
-                (CTLR_ONE::EnableGrp0::CLEAR + CTLR_ONE::EnableGrp1::CLEAR).value
+                (CTLR_NS::EnableGrp1::SET + CTLR_NS::EnableGrp1A::SET).value
             }
+            SecurityState::Single => (CTLR_ONE::EnableGrp0::SET + CTLR_ONE::EnableGrp1::SET).value,
         };
         self.gicd().CTLR.set(old & !val);
         barrier::isb(barrier::SY);
     }
 
+    /// GICD_CTLR affinity routing enable bits for the current security state
+    fn are_bits(&self) -> u32 {
+        match self.security_state {
+            SecurityState::Secure => (CTLR_S::ARE_S::SET + CTLR_S::ARE_NS::SET).value,
+            SecurityState::NonSecure => CTLR_NS::ARE_NS::SET.value,
+            SecurityState::Single => CTLR_ONE::ARE::SET.value,
+        }
+    }
+
+    /// Architecture revision from the first redistributor's GICR_PIDR2
+    fn arch_rev(&self) -> u8 {
+        self.rd_slice()
+            .iter()
+            .next()
+            .map_or(3, |rd| unsafe { rd.as_ref() }.lpi.arch_rev())
+    }
+
     fn rd_slice(&self) -> RDv3Slice {
-        RDv3Slice::new(unsafe { NonNull::new_unchecked(self.gicr.as_ptr()) })
+        RDv3Slice::new(
+            unsafe { NonNull::new_unchecked(self.gicr.as_ptr()) },
+            self.gicr_size,
+        )
+    }
+
+    /// Distance in bytes between the first redistributor and the next one.
+    ///
+    /// `0x20000` for the RD_base and SGI_base frames of GICv3, `0x40000` when
+    /// GICR_TYPER.VLPIS reports the additional GICv4 VLPI_base frames. The
+    /// redistributor walk reads the stride of every frame, so mixed regions
+    /// are handled as well.
+    pub fn redistributor_stride(&self) -> usize {
+        self.rd_slice()
+            .iter()
+            .next()
+            .map_or(GICR_FRAME_STRIDE_V3, |rd| {
+                unsafe { rd.as_ref() }.lpi.frame_stride()
+            })
+    }
+
+    /// Check that the redistributor region contains a frame with
+    /// GICR_TYPER.Last set and return the number of redistributors.
+    ///
+    /// Always succeeds unless the region length was given with
+    /// [`Self::new_with_gicr_size`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::RedistributorRegionTooSmall`] if the region ends
+    /// before the last redistributor.
+    pub fn check_redistributor_region(&self) -> Result<usize, GicError> {
+        let mut count = 0;
+        let mut found_last = false;
+        for rd in self.rd_slice().iter() {
+            count += 1;
+            found_last = unsafe { rd.as_ref() }.lpi.TYPER.is_set(gicr::TYPER::Last);
+        }
+        if found_last {
+            Ok(count)
+        } else {
+            Err(GicError::RedistributorRegionTooSmall)
+        }
     }
 
     fn current_rd_ref(&self) -> &RedistributorV3 {
@@ -437,18 +847,44 @@ impl Gic {
     }
 
     fn current_rd(&self) -> NonNull<RedistributorV3> {
-        let want = (MPIDR_EL1.get() & 0xFFFFFF) as u32;
-
-        for rd in self.rd_slice().iter() {
-            let affi = unsafe { rd.as_ref() }
-                .lpi_ref()
-                .TYPER
-                .read(gicr::TYPER::Affinity) as u32;
-            if affi == want {
-                return rd;
-            }
+        match self.find_rd(MPIDR_EL1.get()) {
+            Some((_, rd)) => rd,
+            None => panic!("No current redistributor"),
         }
-        panic!("No current redistributor")
+    }
+
+    /// Find the redistributor whose GICR_TYPER.Affinity matches all four
+    /// affinity levels of `mpidr`.
+    fn find_rd(&self, mpidr: u64) -> Option<(usize, NonNull<RedistributorV3>)> {
+        let want = Affinity::from_mpidr(mpidr).affinity();
+        self.rd_slice()
+            .iter()
+            .enumerate()
+            .find(|(_, rd)| unsafe { rd.as_ref() }.lpi_ref().get_affinity() == want)
+    }
+
+    /// Index of the redistributor frame that belongs to the CPU with `mpidr`.
+    ///
+    /// Matches Aff3.Aff2.Aff1.Aff0 of the MPIDR against GICR_TYPER.Affinity
+    /// of every frame. The index can be passed to
+    /// [`Self::cpu_interface_by_index`], e.g. for the primary CPU to wake a
+    /// secondary's redistributor before starting it.
+    ///
+    /// Returns `None` if no redistributor reports that affinity.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use arm_gic_driver::{VirtAddr, v3::Gic};
+    /// # let gic = unsafe { Gic::new(VirtAddr::new(0), VirtAddr::new(0)) };
+    /// let secondary_mpidr = 0x8000_0101;
+    /// if let Some(idx) = gic.redistributor_index_for_mpidr(secondary_mpidr) {
+    ///     let cpu = gic.cpu_interface_by_index(idx).unwrap();
+    ///     cpu.init_redistributor().unwrap();
+    /// }
+    /// ```
+    pub fn redistributor_index_for_mpidr(&self, mpidr: u64) -> Option<usize> {
+        self.find_rd(mpidr).map(|(idx, _)| idx)
     }
 
     /// Get a CPU interface for the current CPU.
@@ -473,9 +909,43 @@ impl Gic {
         CpuInterface {
             rd: self.current_rd().as_ptr(),
             security_state: self.security_state,
+            eoi_mode: Cell::new(None),
         }
     }
 
+    /// Get a CPU interface bound to the `idx`-th redistributor in frame order.
+    ///
+    /// Redistributor frames are walked until the one with `GICR_TYPER.Last`
+    /// set, so this works before MPIDR values are meaningful, e.g. to wake
+    /// every redistributor from a boot loop with
+    /// [`CpuInterface::init_redistributor`].
+    ///
+    /// Only the memory-mapped redistributor frames (SGI/PPI configuration,
+    /// wake-up) are valid through the returned handle. Methods that access
+    /// ICC_* system registers always act on the CPU executing them, so they
+    /// must not be used unless `idx` is the current CPU's redistributor. This
+    /// includes [`CpuInterface::ack_and_complete`], whose cached EOI mode is
+    /// read from the executing CPU's ICC_CTLR_EL1 on first use.
+    ///
+    /// Returns `None` if `idx` is past the last redistributor.
+    pub fn cpu_interface_by_index(&self, idx: usize) -> Option<CpuInterface> {
+        let rd = self.rd_slice().iter().nth(idx)?;
+        Some(CpuInterface {
+            rd: rd.as_ptr(),
+            security_state: self.security_state,
+            eoi_mode: Cell::new(None),
+        })
+    }
+
+    /// Get the virtual CPU interface (ICH_*_EL2) of the current CPU.
+    ///
+    /// Returns `None` unless running at EL2, where the hypervisor control
+    /// registers are accessible.
+    #[cfg(target_arch = "aarch64")]
+    pub fn virtual_cpu_interface(&self) -> Option<VirtualCpuInterface> {
+        (CurrentEL.read(CurrentEL::EL) == 2).then(VirtualCpuInterface::new)
+    }
+
     /// Enable or disable a shared peripheral interrupt (SPI).
     ///
     /// This function controls the enable state of SPIs through the distributor.
@@ -489,8 +959,7 @@ impl Gic {
     ///
     /// # Panics
     ///
-    /// Panics if `intid` represents a private interrupt (SGI or PPI).
-    /// Use the CPU interface for private interrupt control.
+    /// Panics if `intid` is not implemented, see [`Self::try_set_irq_enable`].
     ///
     /// # Examples
     ///
@@ -502,15 +971,91 @@ impl Gic {
     /// gic.set_irq_enable(spi, false); // Disable SPI 42
     /// ```
     pub fn set_irq_enable(&mut self, intid: IntId, enable: bool) {
-        if intid.is_private() {
+        if let Err(e) = self.try_set_irq_enable(intid, enable) {
+            panic!("Failed to set irq enable: {e}");
+        }
+    }
+
+    /// Enable or disable an interrupt, checking that it is implemented.
+    ///
+    /// SGIs, PPIs and Extended PPIs are configured in the current CPU's
+    /// redistributor.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::NotImplemented`] if `intid` is neither banked in
+    /// the redistributor nor an SPI implemented according to
+    /// GICD_TYPER.ITLinesNumber.
+    pub fn try_set_irq_enable(&self, intid: IntId, enable: bool) -> Result<(), GicError> {
+        if intid.is_redistributor_banked() {
             self.current_rd_ref()
                 .sgi
                 .set_enable_interrupt(intid, enable);
-        } else if enable {
+            return Ok(());
+        }
+        self.validate_spi(intid)?;
+        if enable {
             self.gicd().irq_enable(intid.to_u32());
         } else {
             self.gicd().irq_disable(intid.to_u32());
         }
+        Ok(())
+    }
+
+    /// Check that `id` is an SPI the distributor implements.
+    ///
+    /// GICD_TYPER.ITLinesNumber bounds the SPIs, which is tighter than the
+    /// INTID width reported by [`Self::max_intid`] as that covers LPIs too.
+    fn validate_spi(&self, id: IntId) -> Result<(), GicError> {
+        if id.classify() == IntClass::Spi && id.to_u32() < self.gicd().max_spi_num() {
+            Ok(())
+        } else {
+            Err(GicError::NotImplemented(id))
+        }
+    }
+
+    /// Enable or disable `count` consecutive interrupts starting at `start`.
+    ///
+    /// Writes are coalesced per 32-bit enable register bank, so a fully covered
+    /// bank costs a single register write. SGIs and PPIs in the range are
+    /// configured in the current CPU's redistributor, SPIs in the distributor.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use arm_gic_driver::{IntId, VirtAddr, v3::Gic};
+    /// # let mut gic = unsafe { Gic::new(VirtAddr::new(0), VirtAddr::new(0)) };
+    /// // Mask SPIs 0..200
+    /// gic.set_irq_enable_range(IntId::spi(0), 200, false);
+    /// ```
+    pub fn set_irq_enable_range(&self, start: IntId, count: u32, enable: bool) {
+        if enable {
+            self.write_irq_bits_range(start, count, |sgi| &sgi.ISENABLER0, &self.gicd().ISENABLER);
+        } else {
+            self.write_irq_bits_range(start, count, |sgi| &sgi.ICENABLER0, &self.gicd().ICENABLER);
+        }
+    }
+
+    /// Write 1 to the bit of `count` interrupts starting at `start` in a
+    /// write-1-to-act register family: SGIs and PPIs go to `rd_reg` of the
+    /// current CPU's redistributor, SPIs to `gicd_regs`, one write per bank.
+    fn write_irq_bits_range(
+        &self,
+        start: IntId,
+        count: u32,
+        rd_reg: impl FnOnce(&SGI) -> &ReadWrite<u32>,
+        gicd_regs: &[ReadWrite<u32>],
+    ) {
+        let start = start.to_u32();
+        let end = start.saturating_add(count);
+        if start < 32 {
+            let reg = rd_reg(&self.current_rd_ref().sgi);
+            core::slice::from_ref(reg).set_irq_bit_range(start, end.min(32) - start);
+        }
+        if end > 32 {
+            let spi_start = start.max(32);
+            gicd_regs.set_irq_bit_range(spi_start, end - spi_start);
+        }
     }
 
     /// Check if an interrupt is enabled.
@@ -538,7 +1083,7 @@ impl Gic {
     /// }
     /// ```
     pub fn is_irq_enable(&self, id: IntId) -> bool {
-        if id.is_private() {
+        if id.is_redistributor_banked() {
             self.current_rd_ref().sgi.is_interrupt_enabled(id)
         } else {
             self.gicd().ISENABLER.get_irq_bit(id.into())
@@ -564,11 +1109,82 @@ impl Gic {
     /// let spi = IntId::spi(42);
     /// gic.set_priority(spi, 0x80); // Set to medium priority
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `intid` is not implemented, see [`Self::try_set_priority`].
     pub fn set_priority(&self, intid: IntId, priority: u8) {
-        if intid.is_private() {
+        if let Err(e) = self.try_set_priority(intid, priority) {
+            panic!("Failed to set priority: {e}");
+        }
+    }
+
+    /// Set the priority of an interrupt, checking that it is implemented.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::NotImplemented`] if `intid` is neither banked in
+    /// the redistributor nor covered by [`Self::priority_slice`].
+    pub fn try_set_priority(&self, intid: IntId, priority: u8) -> Result<(), GicError> {
+        if intid.is_redistributor_banked() {
             self.current_rd_ref().sgi.set_priority(intid, priority);
         } else {
-            self.gicd().set_priority(intid.to_u32(), priority);
+            self.validate_spi(intid)?;
+            self.priority_slice()[intid.to_u32() as usize].set(priority);
+        }
+        Ok(())
+    }
+
+    /// `GICD_IPRIORITYR<n>` of the implemented SPIs, indexed by interrupt ID.
+    ///
+    /// The slice ends at the last ID implemented according to
+    /// GICD_TYPER.ITLinesNumber (at most 1019), so bulk updates never reach
+    /// reserved registers. Entries below 32 are RES0 with affinity routing;
+    /// SGI and PPI priorities are held by the redistributors.
+    pub fn priority_slice(&self) -> &[ReadWrite<u8>] {
+        let end = self.gicd().max_spi_num().min(SPECIAL_RANGE_START);
+        &self.gicd().IPRIORITYR[..end as usize]
+    }
+
+    /// Set the priority of every interrupt in `range` for which `f` returns `Some`.
+    ///
+    /// SGIs, PPIs and Extended PPIs are written to the redistributor of the
+    /// calling CPU, which is looked up once. SPIs covered by
+    /// [`Self::priority_slice`] are written to the distributor. All other IDs
+    /// (unimplemented SPIs, special IDs, Extended SPIs and LPIs) are skipped
+    /// without calling `f`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use arm_gic_driver::{IntId, VirtAddr, v3::Gic};
+    /// # let gic = unsafe { Gic::new(VirtAddr::new(0), VirtAddr::new(0)) };
+    /// // Timer PPIs high, everything else in 32..64 at medium priority
+    /// gic.set_priorities(16..64, |id| match id.to_u32() {
+    ///     27 | 30 => Some(0x20),
+    ///     32.. => Some(0x80),
+    ///     _ => None,
+    /// });
+    /// ```
+    pub fn set_priorities<F: Fn(IntId) -> Option<u8>>(&self, range: Range<u32>, f: F) {
+        let spis = self.priority_slice();
+        let mut rd = None;
+        for raw in range {
+            let id = unsafe { IntId::raw(raw) };
+            let banked = id.is_redistributor_banked();
+            if !banked && (id.classify() != IntClass::Spi || raw as usize >= spis.len()) {
+                continue;
+            }
+            let Some(priority) = f(id) else {
+                continue;
+            };
+            if banked {
+                rd.get_or_insert_with(|| self.current_rd_ref())
+                    .sgi
+                    .set_priority(id, priority);
+            } else {
+                spis[raw as usize].set(priority);
+            }
         }
     }
 
@@ -593,11 +1209,29 @@ impl Gic {
     /// let priority = gic.get_priority(spi);
     /// println!("SPI 42 priority: {}", priority);
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `intid` is not implemented, see [`Self::try_get_priority`].
     pub fn get_priority(&self, intid: IntId) -> u8 {
-        if intid.is_private() {
-            self.current_rd_ref().sgi.get_priority(intid)
+        match self.try_get_priority(intid) {
+            Ok(priority) => priority,
+            Err(e) => panic!("Failed to get priority: {e}"),
+        }
+    }
+
+    /// Get the priority of an interrupt, checking that it is implemented.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::NotImplemented`] if `intid` is neither banked in
+    /// the redistributor nor covered by [`Self::priority_slice`].
+    pub fn try_get_priority(&self, intid: IntId) -> Result<u8, GicError> {
+        if intid.is_redistributor_banked() {
+            Ok(self.current_rd_ref().sgi.get_priority(intid))
         } else {
-            self.gicd().get_priority(intid.to_u32())
+            self.validate_spi(intid)?;
+            Ok(self.priority_slice()[intid.to_u32() as usize].get())
         }
     }
 
@@ -621,7 +1255,7 @@ impl Gic {
     /// gic.set_active(spi, false); // Clear active state
     /// ```
     pub fn set_active(&self, id: IntId, active: bool) {
-        if id.is_private() {
+        if id.is_redistributor_banked() {
             self.current_rd_ref().sgi.set_active(id, active);
         } else if active {
             self.gicd().ISACTIVER.set_irq_bit(id.into());
@@ -653,7 +1287,7 @@ impl Gic {
     /// }
     /// ```
     pub fn is_active(&self, id: IntId) -> bool {
-        if id.is_private() {
+        if id.is_redistributor_banked() {
             self.current_rd_ref().sgi.is_active(id)
         } else {
             self.gicd().ISACTIVER.get_irq_bit(id.into())
@@ -679,8 +1313,14 @@ impl Gic {
     /// gic.set_pending(spi, true);  // Trigger interrupt
     /// gic.set_pending(spi, false); // Clear pending state
     /// ```
+    ///
+    /// SGI and PPI pending state is always held in the current CPU's
+    /// GICR_ISPENDR0 while affinity routing is enabled, including when
+    /// GICD_CTLR.nASSGIreq is set (see [`Self::is_sgi_active_state_disabled`]).
+    /// The legacy GICD_SPENDSGIR/CPENDSGIR registers are only used with
+    /// affinity routing disabled, which [`Self::init`] never configures.
     pub fn set_pending(&self, id: IntId, pending: bool) {
-        if id.is_private() {
+        if id.is_redistributor_banked() {
             self.current_rd_ref().sgi.set_pending(id, pending);
         } else if pending {
             self.gicd().set_pending(id.into());
@@ -689,9 +1329,76 @@ impl Gic {
         }
     }
 
-    /// Check if an interrupt is pending.
+    /// Clear the pending state of an interrupt.
     ///
-    /// Returns whether the specified interrupt is currently pending.
+    /// Writes GICD_ICPENDR for SPIs and the current CPU's GICR_ICPENDR0 for
+    /// SGIs and PPIs, like `set_pending(id, false)`.
+    ///
+    /// For a level-sensitive interrupt this only removes pending state that
+    /// was latched by software (GICx_ISPENDR). While the device still asserts
+    /// its interrupt line the interrupt stays pending, so silence the device
+    /// first. For an edge-triggered interrupt the latched edge is discarded.
+    /// See [`Self::scrub_pending`] for a sequence that cannot race with the
+    /// interrupt being signalled to a CPU.
+    pub fn clear_pending(&self, id: IntId) {
+        self.set_pending(id, false);
+    }
+
+    /// Disable an interrupt, clear its pending state and enable it again.
+    ///
+    /// The interrupt is only re-enabled if it was enabled before. Waiting
+    /// for GICD_CTLR.RWP (SPIs) or GICR_CTLR.RWP (SGIs and PPIs) after the
+    /// disable guarantees the stale pending state can no longer be forwarded
+    /// to a CPU while it is cleared. The level-sensitive caveat of
+    /// [`Self::clear_pending`] applies: [`Self::is_pending`] still reports
+    /// the interrupt if the device keeps asserting it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::RwpTimeout`] if the disable does not complete; the
+    /// interrupt is then left disabled and its pending state is not cleared.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use arm_gic_driver::{IntId, VirtAddr, v3::Gic};
+    /// # let gic = unsafe { Gic::new(VirtAddr::new(0), VirtAddr::new(0)) };
+    /// // The device condition was cleared, drop the stale pending state
+    /// gic.scrub_pending(IntId::spi(42)).unwrap();
+    /// ```
+    pub fn scrub_pending(&self, id: IntId) -> Result<(), GicError> {
+        let enabled = self.is_irq_enable(id);
+        if enabled {
+            if id.is_redistributor_banked() {
+                let rd = self.current_rd_ref();
+                rd.sgi.set_enable_interrupt(id, false);
+                rd.lpi.wait_for_rwp()?;
+            } else {
+                self.gicd().irq_disable(id.to_u32());
+                self.gicd().wait_for_rwp()?;
+            }
+        }
+
+        self.clear_pending(id);
+
+        if enabled {
+            if id.is_redistributor_banked() {
+                self.current_rd_ref().sgi.set_enable_interrupt(id, true);
+            } else {
+                self.gicd().irq_enable(id.to_u32());
+            }
+        }
+        Ok(())
+    }
+
+    /// Check if an interrupt is pending.
+    ///
+    /// Returns whether the specified interrupt is currently pending.
+    ///
+    /// SGIs and PPIs are read from the same location as in [`Self::set_pending`].
+    /// With affinity routing GICR_ISPENDR0 holds a single pending bit per
+    /// SGI, so the sending CPU is not recorded; see
+    /// [`Self::sgi_pending_sources`] for the legacy per-source state.
     ///
     /// # Arguments
     ///
@@ -712,13 +1419,116 @@ impl Gic {
     /// }
     /// ```
     pub fn is_pending(&self, id: IntId) -> bool {
-        if id.is_private() {
+        if id.is_redistributor_banked() {
             self.current_rd_ref().sgi.is_pending(id)
         } else {
             self.gicd().ISPENDR.get_irq_bit(id.into())
         }
     }
 
+    /// Get the CPUs with a pending instance of an SGI from GICD_SPENDSGIR.
+    ///
+    /// Bit `n` of the result is set if CPU interface `n` sent `id` to this
+    /// CPU and it is still pending. The per-source state only exists with
+    /// affinity routing disabled (legacy operation), which [`Self::init`]
+    /// never configures; with affinity routing an SGI has one pending bit in
+    /// GICR_ISPENDR0, see [`Self::is_pending`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::AffinityRoutingEnabled`] if affinity routing is
+    /// enabled for the current security state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not an SGI.
+    pub fn sgi_pending_sources(&self, id: IntId) -> Result<u8, GicError> {
+        let (reg, shift) = self.legacy_sgi_byte(id)?;
+        Ok((self.gicd().SPENDSGIR[reg].get() >> shift) as u8)
+    }
+
+    /// Clear the pending state of an SGI sent by the CPUs in `sources`
+    /// through GICD_CPENDSGIR.
+    ///
+    /// `sources` uses the layout of [`Self::sgi_pending_sources`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::AffinityRoutingEnabled`] if affinity routing is
+    /// enabled for the current security state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not an SGI.
+    pub fn clear_sgi_pending_sources(&self, id: IntId, sources: u8) -> Result<(), GicError> {
+        let (reg, shift) = self.legacy_sgi_byte(id)?;
+        self.gicd().CPENDSGIR[reg].set((sources as u32) << shift);
+        Ok(())
+    }
+
+    /// Register index and bit shift of the byte of SGI `id` in
+    /// GICD_SPENDSGIR/CPENDSGIR.
+    fn legacy_sgi_byte(&self, id: IntId) -> Result<(usize, u32), GicError> {
+        assert!(id.is_sgi(), "{id:?} is not an SGI");
+        if self.affinity_routing_enabled() {
+            return Err(GicError::AffinityRoutingEnabled);
+        }
+        let sgi = id.to_u32();
+        Ok(((sgi / 4) as usize, (sgi % 4) * 8))
+    }
+
+    /// Clear the pending state of `count` consecutive interrupts starting at `start`.
+    ///
+    /// Writes whole GICR_ICPENDR0/GICD_ICPENDR banks where the range covers
+    /// them and leaves every other interrupt untouched, e.g. to scrub the
+    /// interrupts of one device during error recovery. SGIs and PPIs in the
+    /// range are cleared in the current CPU's redistributor. Extended SPI
+    /// and PPI ranges are not covered.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use arm_gic_driver::{IntId, VirtAddr, v3::Gic};
+    /// # let gic = unsafe { Gic::new(VirtAddr::new(0), VirtAddr::new(0)) };
+    /// // Drop anything pending on SPIs 64..96
+    /// gic.clear_pending_range(IntId::spi(64), 32);
+    /// ```
+    pub fn clear_pending_range(&self, start: IntId, count: u32) {
+        self.write_irq_bits_range(start, count, |sgi| &sgi.ICPENDR0, &self.gicd().ICPENDR);
+    }
+
+    /// Clear the active state of `count` consecutive interrupts starting at `start`.
+    ///
+    /// Same register layout and scope as [`Self::clear_pending_range`], using
+    /// GICR_ICACTIVER0/GICD_ICACTIVER.
+    pub fn clear_active_range(&self, start: IntId, count: u32) {
+        self.write_irq_bits_range(start, count, |sgi| &sgi.ICACTIVER0, &self.gicd().ICACTIVER);
+    }
+
+    /// Iterate over all pending SPIs without acknowledging them.
+    ///
+    /// Walks GICD_ISPENDR up to the last interrupt implemented according to
+    /// GICD_TYPER.ITLinesNumber; each register bank is read when the iterator
+    /// reaches it. Pending private
+    /// interrupts are reported by [`CpuInterface::pending_iter`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use arm_gic_driver::{VirtAddr, v3::Gic};
+    /// # let gic = unsafe { Gic::new(VirtAddr::new(0), VirtAddr::new(0)) };
+    /// for id in gic.pending_iter() {
+    ///     println!("{id:?} is pending");
+    /// }
+    /// ```
+    pub fn pending_iter(&self) -> impl Iterator<Item = IntId> + '_ {
+        let gicd = self.gicd();
+        let end = gicd.max_spi_num().min(SPECIAL_RANGE_START);
+        (1..end.div_ceil(32))
+            .flat_map(move |reg| iter_irq_bits(gicd.ISPENDR[reg as usize].get(), reg * 32))
+            .take_while(move |id| id.to_u32() < end)
+    }
+
     /// Get the raw IIDR (Implementer Identification Register) value.
     ///
     /// Returns the raw GICD_IIDR register value which contains
@@ -741,6 +1551,12 @@ impl Gic {
         self.gicd().IIDR.get()
     }
 
+    /// Get the decoded GICD_IIDR, e.g. to print "Arm GIC-600 r1p6" or to
+    /// apply implementation specific quirks.
+    pub fn iidr(&self) -> Iidr {
+        Iidr::new(self.iidr_raw(), self.arch_rev())
+    }
+
     /// Get the raw TYPER (Type Register) value.
     ///
     /// Returns the raw GICD_TYPER register value which contains
@@ -764,6 +1580,96 @@ impl Gic {
         self.gicd().TYPER.get()
     }
 
+    /// Place an interrupt in Secure Group 1 (IGROUPR = 0, IGRPMODR = 1).
+    ///
+    /// SPIs are configured in the distributor, SGIs and PPIs in the current
+    /// CPU's redistributor. Only meaningful with two security states when
+    /// called from the Secure state; otherwise the registers are RAZ/WI.
+    pub fn set_group1_secure(&self, id: IntId) {
+        self.set_interrupt_group(id, InterruptGroup::Group1Secure);
+    }
+
+    /// Place an interrupt in `group` by writing its IGROUPR and IGRPMODR bits.
+    ///
+    /// SPIs are configured in the distributor, SGIs and PPIs in the current
+    /// CPU's redistributor. GICD_IGRPMODR is RAZ/WI unless accessed from the
+    /// Secure state with two security states, so outside of it
+    /// [`InterruptGroup::Group1Secure`] ends up as Group 0.
+    pub fn set_interrupt_group(&self, id: IntId, group: InterruptGroup) {
+        if id.is_redistributor_banked() {
+            let (group1, modifier) = group.bits();
+            let sgi = &self.current_rd_ref().sgi;
+            sgi.set_group(id, group1);
+            sgi.set_group_modifier(id, modifier);
+        } else {
+            self.gicd().set_interrupt_group(id.to_u32(), group);
+        }
+    }
+
+    /// Get the group of an interrupt from its IGROUPR and IGRPMODR bits.
+    ///
+    /// SGIs, PPIs and Extended PPIs are read from the current CPU's
+    /// redistributor.
+    pub fn get_interrupt_group(&self, id: IntId) -> InterruptGroup {
+        let (group1, modifier) = if id.is_redistributor_banked() {
+            let sgi = &self.current_rd_ref().sgi;
+            (sgi.is_group1(id), sgi.is_group_modifier(id))
+        } else {
+            self.gicd().interrupt_group(id.to_u32())
+        };
+        InterruptGroup::from_bits(group1, modifier)
+    }
+
+    /// Implemented Extended SPI INTIDs (4096 onwards), from GICD_TYPER.ESPI_range.
+    ///
+    /// Returns `None` if Extended SPIs are not supported.
+    pub fn espi_range(&self) -> Option<Range<u32>> {
+        let count = self.gicd().extended_spi_range();
+        (count > 0).then(|| EXTENDED_SPI_BASE..EXTENDED_SPI_BASE + count)
+    }
+
+    /// Implemented Extended PPI INTIDs (1056 onwards), from GICR_TYPER.PPInum
+    /// of the first redistributor.
+    ///
+    /// Returns `None` if Extended PPIs are not supported.
+    pub fn eppi_range(&self) -> Option<Range<u32>> {
+        let count = self
+            .rd_slice()
+            .iter()
+            .next()
+            .map_or(0, |rd| unsafe { rd.as_ref() }.lpi.extended_ppi_count());
+        (count > 0).then(|| EXTENDED_PPI_BASE..EXTENDED_PPI_BASE + count)
+    }
+
+    /// Decode GICD_TYPER and GICD_TYPER2.
+    ///
+    /// The Extended PPI range is reported by the redistributors, so it is
+    /// taken from the first redistributor's GICR_TYPER.
+    pub fn typer(&self) -> TyperInfo {
+        let gicd = self.gicd();
+        let typer = gicd.TYPER.extract();
+        let typer2 = gicd.TYPER2.extract();
+        TyperInfo {
+            id_bits: typer.read(gicd::TYPER::IDbits) + 1,
+            spi_count: gicd.max_spi_num().min(SPECIAL_RANGE_START) - 32,
+            cpu_num: typer.read(gicd::TYPER::CPUNumber) + 1,
+            security_extensions: typer.is_set(gicd::TYPER::SecurityExtn),
+            lpis: typer.is_set(gicd::TYPER::LPIS),
+            mbis: typer.is_set(gicd::TYPER::MBIS),
+            dvis: typer.is_set(gicd::TYPER::DVIS),
+            nmi: typer.is_set(gicd::TYPER::NMI),
+            affinity3: typer.is_set(gicd::TYPER::A3V),
+            one_of_n: !typer.is_set(gicd::TYPER::No1N),
+            rss: typer.is_set(gicd::TYPER::RSS),
+            extended_spi: self.espi_range(),
+            extended_ppi: self.eppi_range(),
+            vpe_id_bits: typer2
+                .is_set(TYPER2::VIL)
+                .then(|| typer2.read(TYPER2::VID) + 1),
+            nassgi_capable: typer2.is_set(TYPER2::nASSGIcap),
+        }
+    }
+
     /// Set the trigger type configuration for an interrupt.
     ///
     /// Configures whether an interrupt is triggered by signal edges or levels.
@@ -783,19 +1689,38 @@ impl Gic {
     /// gic.set_cfg(spi, Trigger::Edge);  // Configure as edge-triggered
     /// gic.set_cfg(spi, Trigger::Level); // Configure as level-triggered
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not implemented, see [`Self::try_set_cfg`].
     pub fn set_cfg(&self, id: IntId, cfg: Trigger) {
-        if id.is_private() {
+        if let Err(e) = self.try_set_cfg(id, cfg) {
+            panic!("Failed to set trigger: {e}");
+        }
+    }
+
+    /// Set the trigger type of an interrupt, checking that it is implemented.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::NotImplemented`] if `id` is neither banked in the
+    /// redistributor nor an SPI implemented according to
+    /// GICD_TYPER.ITLinesNumber.
+    pub fn try_set_cfg(&self, id: IntId, cfg: Trigger) -> Result<(), GicError> {
+        if id.is_redistributor_banked() {
             // Apply to all redistributors since private interrupts are per-CPU
             for rd in self.rd_slice().iter() {
                 unsafe { rd.as_ref() }.sgi.set_cfgr(id, cfg);
             }
         } else {
+            self.validate_spi(id)?;
             self.gicd().set_interrupt_config(id, cfg);
         }
+        Ok(())
     }
 
     pub fn get_cfg(&self, id: IntId) -> Trigger {
-        if id.is_private() {
+        if id.is_redistributor_banked() {
             self.current_rd_ref().sgi.get_cfgr(id)
         } else {
             let int_num = id.to_u32();
@@ -803,7 +1728,7 @@ impl Gic {
             let bit_offset = (int_num % 16) * 2 + 1; // Each interrupt uses 2 bits, we use bit 1 for edge/level
 
             assert!(
-                reg_index < self.gicd().ICFGR.len(),
+                reg_index < self.icfgr_count(),
                 "Invalid interrupt ID for config: {id:?}"
             );
 
@@ -819,28 +1744,982 @@ impl Gic {
     }
 
     /// If `affinity` is `None`, interrupts routed to any PE defined as a participating node.
-    pub fn set_target_cpu(&self, id: IntId, affinity: Option<Affinity>) {
+    ///
+    /// Shorthand for [`Gic::set_routing`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Gic::set_routing`].
+    pub fn set_target_cpu(&self, id: IntId, affinity: Option<Affinity>) -> Result<(), GicError> {
+        self.set_routing(id, affinity.map_or(Routing::OneOfN, Routing::Specific))
+    }
+
+    /// Like [`Gic::set_target_cpu`], but rejects a specific affinity that
+    /// no redistributor reports (see [`Gic::is_valid_affinity`]) instead of
+    /// programming a GICD_IROUTER value that never delivers.
+    ///
+    /// # Errors
+    ///
+    /// - [`GicError::UnknownAffinity`] if `affinity` is not present.
+    /// - Otherwise see [`Gic::set_routing`].
+    pub fn set_target_cpu_checked(
+        &self,
+        id: IntId,
+        affinity: Option<Affinity>,
+    ) -> Result<(), GicError> {
+        if let Some(aff) = affinity
+            && !self.is_valid_affinity(aff)
+        {
+            return Err(GicError::UnknownAffinity(aff.affinity()));
+        }
+        self.set_target_cpu(id, affinity)
+    }
+
+    /// Check whether a redistributor with the given affinity exists.
+    ///
+    /// Scans GICR_TYPER.Affinity of every redistributor frame, e.g. to reject
+    /// device tree CPUs that are not present before routing to them.
+    pub fn is_valid_affinity(&self, aff: Affinity) -> bool {
+        let want = aff.affinity();
+        self.rd_slice()
+            .iter()
+            .any(|rd| unsafe { rd.as_ref() }.lpi_ref().get_affinity() == want)
+    }
+
+    /// Route an SPI to the CPU executing this call.
+    ///
+    /// Same as [`Gic::route_to_current_cpu`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Gic::set_routing`].
+    #[deprecated(note = "use route_to_current_cpu")]
+    pub fn set_target_current(&self, id: IntId) -> Result<(), GicError> {
+        self.route_to_current_cpu(id)
+    }
+
+    /// Route an SPI to the CPU executing this call.
+    ///
+    /// Shorthand for [`Gic::set_routing`] with the affinity read from
+    /// MPIDR_EL1, for drivers that bind their interrupt to the CPU they are
+    /// initialized on.
+    ///
+    /// # Errors
+    ///
+    /// See [`Gic::set_routing`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use arm_gic_driver::{IntId, VirtAddr, v3::Gic};
+    /// # let gic = unsafe { Gic::new(VirtAddr::new(0), VirtAddr::new(0)) };
+    /// gic.route_to_current_cpu(IntId::spi(10)).unwrap();
+    /// ```
+    pub fn route_to_current_cpu(&self, id: IntId) -> Result<(), GicError> {
+        self.set_routing(id, Routing::Specific(Affinity::current()))
+    }
+
+    /// Route an SPI to any participating CPU (1-of-N distribution).
+    ///
+    /// Shorthand for [`Gic::set_routing`] with [`Routing::OneOfN`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Gic::set_routing`].
+    pub fn route_to_any(&self, id: IntId) -> Result<(), GicError> {
+        self.set_routing(id, Routing::OneOfN)
+    }
+
+    pub fn get_target_cpu(&self, id: IntId) -> Option<Affinity> {
+        match self.get_routing(id) {
+            Routing::Specific(aff) => Some(aff),
+            Routing::OneOfN => None,
+        }
+    }
+
+    /// Set the routing mode of an SPI (GICD_IROUTER).
+    ///
+    /// # Errors
+    ///
+    /// - [`GicError::Affinity3NotSupported`] if `aff3` of a specific target is
+    ///   non-zero but the GIC does not implement affinity level 3
+    ///   (see [`Gic::supports_affinity3`]).
+    /// - [`GicError::OneOfNNotSupported`] if [`Routing::OneOfN`] is requested
+    ///   but the GIC does not support it (see [`Gic::supports_1_of_n`]).
+    /// - [`GicError::NotImplemented`] if `id` is not an SPI implemented
+    ///   according to GICD_TYPER.ITLinesNumber.
+    pub fn set_routing(&self, id: IntId, routing: Routing) -> Result<(), GicError> {
         // Only SPIs (Shared Peripheral Interrupts) can have their target CPU set
         // SGIs and PPIs are always private to a specific CPU core
         assert!(
             !id.is_private(),
             "Cannot set target CPU for private interrupt (SGI/PPI): {id:?}"
         );
+        self.validate_spi(id)?;
+        let affinity = match routing {
+            Routing::Specific(aff) => {
+                if aff.aff3 != 0 && !self.supports_affinity3() {
+                    return Err(GicError::Affinity3NotSupported);
+                }
+                Some(aff)
+            }
+            Routing::OneOfN => {
+                if !self.supports_1_of_n() {
+                    return Err(GicError::OneOfNNotSupported);
+                }
+                None
+            }
+        };
         self.gicd().set_interrupt_route(id.to_u32(), affinity);
+        Ok(())
     }
 
-    pub fn get_target_cpu(&self, id: IntId) -> Option<Affinity> {
+    /// Get the routing mode of an SPI (GICD_IROUTER).
+    pub fn get_routing(&self, id: IntId) -> Routing {
         // Only SPIs (Shared Peripheral Interrupts) can have their target CPU set
         // SGIs and PPIs are always private to a specific CPU core
         assert!(
             !id.is_private(),
             "Cannot get target CPU for private interrupt (SGI/PPI): {id:?}"
         );
-        self.gicd().get_interrupt_route(id.to_u32())
+        match self.gicd().get_interrupt_route(id.to_u32()) {
+            Some(aff) => Routing::Specific(aff),
+            None => Routing::OneOfN,
+        }
+    }
+
+    /// List the PEs an SPI can currently be delivered to.
+    ///
+    /// For [`Routing::OneOfN`] this yields the affinity of every
+    /// redistributor whose GICR_CTLR.DPG bit for the interrupt's group is
+    /// clear (see [`CpuInterface::set_participating`]). For
+    /// [`Routing::Specific`] it yields the target if a matching
+    /// redistributor exists.
+    ///
+    /// The CPU interface group enables (ICC_IGRPEN*_EL1) are not visible to
+    /// other PEs, so a listed PE may still not take the interrupt.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is a private interrupt (SGI/PPI).
+    pub fn eligible_cpus_for(&self, id: IntId) -> impl Iterator<Item = Affinity> {
+        let routing = self.get_routing(id);
+        let group = self.get_interrupt_group(id);
+        self.rd_slice().iter().filter_map(move |rd| {
+            let lpi = unsafe { rd.as_ref() }.lpi_ref();
+            let affinity = Affinity::from_affinity(lpi.get_affinity());
+            let eligible = match routing {
+                Routing::Specific(target) => target == affinity,
+                Routing::OneOfN => lpi.is_participating(group),
+            };
+            eligible.then_some(affinity)
+        })
+    }
+
+    /// Replace the routing of an SPI and return the previous routing.
+    ///
+    /// Intended for IRQ migration, so the caller can record where to move
+    /// the interrupt back to. On error GICD_IROUTER is left unchanged.
+    ///
+    /// GICD_IROUTER has no atomic exchange; the read and the write are two
+    /// accesses, and callers migrating the same interrupt from several CPUs
+    /// must serialize. An interrupt already forwarded to the old target
+    /// before the write is still delivered there.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::set_routing`].
+    pub fn swap_target(&self, id: IntId, routing: Routing) -> Result<Routing, GicError> {
+        let old = self.get_routing(id);
+        self.set_routing(id, routing)?;
+        Ok(old)
+    }
+
+    pub fn max_cpu_num(&self) -> usize {
+        self.gicd().max_cpu_num() as _
+    }
+
+    /// Number of implemented GICD_ICFGR registers.
+    ///
+    /// Derived from GICD_TYPER.ITLinesNumber; bulk trigger configuration should
+    /// only touch registers below this count.
+    pub fn icfgr_count(&self) -> usize {
+        self.gicd().icfgr_count()
+    }
+
+    /// Collect the main implementation parameters of this GIC in one call.
+    ///
+    /// Intended for a boot-time log line, e.g. `info!("{}", gic.summary())`.
+    ///
+    /// The priority bit count is read from ICC_CTLR_EL1 of the calling CPU, so
+    /// this must be called after the current CPU interface has been initialized.
+    pub fn summary(&self) -> GicSummary {
+        let iidr = self.gicd().IIDR.extract();
+        GicSummary {
+            arch_rev: self.arch_rev(),
+            implementer: iidr.read(IIDR::Implementer) as u16,
+            product_id: iidr.read(IIDR::ProductId) as u8,
+            variant: iidr.read(IIDR::Variant) as u8,
+            revision: iidr.read(IIDR::Revision) as u8,
+            spi_count: self.gicd().max_spi_num().min(SPECIAL_RANGE_START) - 32,
+            max_cpu_num: self.max_cpu_num(),
+            redistributor_count: self.rd_slice().iter().count(),
+            security_state: self.security_state,
+            affinity_routing: self.affinity_routing_enabled(),
+            lpis: self.gicd().has_lpis(),
+            priority_bits: ICC_CTLR_EL1.read(ICC_CTLR_EL1::PRIBITS) as u8 + 1,
+        }
+    }
+
+    /// Check whether GICD_CTLR.nASSGIreq is set.
+    ///
+    /// When set, SGIs are delivered without an active state: acknowledging an
+    /// SGI clears its pending state and it never becomes active, so
+    /// [`Self::is_active`] reads `false` for SGIs. Pending state is unaffected
+    /// and stays in GICR_ISPENDR0. Always `false` unless
+    /// [`TyperInfo::nassgi_capable`] is reported.
+    pub fn is_sgi_active_state_disabled(&self) -> bool {
+        self.gicd().CTLR.is_set(CTLR_BASE::nASSGIreq)
+    }
+
+    /// Check whether SGIs can be delivered without an active state
+    /// (GICD_TYPER2.nASSGIcap, GICv4.1).
+    pub fn supports_nassgi(&self) -> bool {
+        self.gicd().TYPER2.is_set(TYPER2::nASSGIcap)
+    }
+
+    /// Select how SGIs are delivered by programming GICD_CTLR.nASSGIreq.
+    ///
+    /// With `legacy` set, SGIs have an active state and must be deactivated
+    /// like any other interrupt. Otherwise acknowledging an SGI only clears
+    /// its pending bit in GICR_ISPENDR0, see
+    /// [`Self::is_sgi_active_state_disabled`].
+    ///
+    /// nASSGIreq may only change while the distributor is disabled, so the
+    /// group enables are cleared around the write and restored afterwards.
+    /// SGIs in flight during the switch are delivered in either mode.
+    ///
+    /// # Errors
+    ///
+    /// - [`GicError::NassgiNotSupported`] if `legacy` is `false` and
+    ///   [`Self::supports_nassgi`] is `false`.
+    /// - [`GicError::RwpTimeout`] if GICD_CTLR.RWP does not clear.
+    pub fn set_affinity_sgi_mode(&self, legacy: bool) -> Result<(), GicError> {
+        if !legacy && !self.supports_nassgi() {
+            return Err(GicError::NassgiNotSupported);
+        }
+        if self.is_sgi_active_state_disabled() != legacy {
+            return Ok(());
+        }
+        let gicd = self.gicd();
+        let old = gicd.CTLR.extract();
+
+        self.disable();
+        gicd.wait_for_rwp()?;
+
+        let mut ctlr = gicd.CTLR.extract();
+        ctlr.modify(if legacy {
+            CTLR_BASE::nASSGIreq::CLEAR
+        } else {
+            CTLR_BASE::nASSGIreq::SET
+        });
+        gicd.CTLR.set(ctlr.get());
+        gicd.wait_for_rwp()?;
+
+        // Restore the group enables that were set before the switch
+        gicd.CTLR
+            .set(gicd.CTLR.get() | (old.get() & !CTLR_BASE::nASSGIreq::SET.value));
+        gicd.wait_for_rwp()
+    }
+
+    /// Query the affinity routing enable bits as `(ARE_S, ARE_NS)`.
+    ///
+    /// In the single security state both values reflect the single ARE bit.
+    /// ARE_S is not visible from Non-secure state and reads as `false`.
+    pub fn is_affinity_routing_enabled(&self) -> (bool, bool) {
+        let ctlr = self.control_state();
+        (ctlr.are_secure, ctlr.are_nonsecure)
+    }
+
+    fn affinity_routing_enabled(&self) -> bool {
+        let ctlr = self.control_state();
+        match self.security_state {
+            SecurityState::Secure => ctlr.are_secure && ctlr.are_nonsecure,
+            SecurityState::NonSecure => ctlr.are_nonsecure,
+            SecurityState::Single => ctlr.are_secure,
+        }
+    }
+
+    /// Check whether the distributor forwards any interrupt group.
+    ///
+    /// `false` means no interrupt is delivered, e.g. before [`Self::init`]
+    /// or after the distributor was disabled by someone else.
+    pub fn is_enabled(&self) -> bool {
+        let groups = self.enabled_groups();
+        groups.group0 || groups.group1_nonsecure || groups.group1_secure
+    }
+
+    /// Decode the GICD_CTLR group enables for the detected security state.
+    ///
+    /// Groups that are not visible from the current security view read as
+    /// disabled, see [`Self::control_state`].
+    pub fn enabled_groups(&self) -> EnabledGroups {
+        let ctlr = self.control_state();
+        EnabledGroups {
+            group0: ctlr.grp0_enabled,
+            group1_nonsecure: ctlr.grp1_enabled,
+            group1_secure: ctlr.grp1_secure_enabled,
+        }
+    }
+
+    /// Decode GICD_CTLR according to the detected security state.
+    ///
+    /// Fields that are not visible from the current security view read as
+    /// `false`. In the single security state both ARE fields reflect the
+    /// single ARE bit.
+    pub fn control_state(&self) -> CtlrState {
+        let ctlr = self.gicd().CTLR.get();
+        match self.security_state {
+            SecurityState::Secure => {
+                let ctlr = LocalRegisterCopy::<u32, CTLR_S::Register>::new(ctlr);
+                CtlrState {
+                    grp0_enabled: ctlr.is_set(CTLR_S::EnableGrp0),
+                    grp1_enabled: ctlr.is_set(CTLR_S::EnableGrp1NS),
+                    grp1_secure_enabled: ctlr.is_set(CTLR_S::EnableGrp1S),
+                    are_secure: ctlr.is_set(CTLR_S::ARE_S),
+                    are_nonsecure: ctlr.is_set(CTLR_S::ARE_NS),
+                    ds: ctlr.is_set(CTLR_S::DS),
+                }
+            }
+            SecurityState::NonSecure => {
+                let ctlr = LocalRegisterCopy::<u32, CTLR_NS::Register>::new(ctlr);
+                CtlrState {
+                    grp0_enabled: false,
+                    grp1_enabled: ctlr.is_set(CTLR_NS::EnableGrp1)
+                        || ctlr.is_set(CTLR_NS::EnableGrp1A),
+                    grp1_secure_enabled: false,
+                    are_secure: false,
+                    are_nonsecure: ctlr.is_set(CTLR_NS::ARE_NS),
+                    ds: false,
+                }
+            }
+            SecurityState::Single => {
+                let ctlr = LocalRegisterCopy::<u32, CTLR_ONE::Register>::new(ctlr);
+                let are = ctlr.is_set(CTLR_ONE::ARE);
+                CtlrState {
+                    grp0_enabled: ctlr.is_set(CTLR_ONE::EnableGrp0),
+                    grp1_enabled: ctlr.is_set(CTLR_ONE::EnableGrp1),
+                    grp1_secure_enabled: false,
+                    are_secure: are,
+                    are_nonsecure: are,
+                    ds: ctlr.is_set(CTLR_ONE::DS),
+                }
+            }
+        }
+    }
+
+    /// Start configuring an interrupt with an [`IrqSetup`] builder.
+    ///
+    /// The builder collects priority, trigger, routing and enable settings and
+    /// writes them in a safe order when [`IrqSetup::apply`] is called.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use arm_gic_driver::{IntId, Trigger, VirtAddr, v3::{Affinity, Gic}};
+    /// # let mut gic = unsafe { Gic::new(VirtAddr::new(0), VirtAddr::new(0)) };
+    /// gic.configure(IntId::spi(42))
+    ///     .priority(0x80)
+    ///     .trigger(Trigger::Level)
+    ///     .target(Affinity::current())
+    ///     .enable()
+    ///     .apply()
+    ///     .unwrap();
+    /// ```
+    pub fn configure(&mut self, id: IntId) -> IrqSetup<'_> {
+        IrqSetup {
+            gic: self,
+            id,
+            priority: None,
+            trigger: None,
+            target: None,
+            enable: false,
+        }
+    }
+
+    /// Check whether the GIC supports affinity level 3.
+    ///
+    /// Reflects GICD_TYPER.A3V. When this returns `false`, `aff3` must be zero
+    /// in interrupt routing and SGI target values.
+    pub fn supports_affinity3(&self) -> bool {
+        self.typer().affinity3
+    }
+
+    /// Cross-check GICD_TYPER.A3V against the redistributor affinities.
+    ///
+    /// Walks every redistributor frame and verifies that its
+    /// `GICR_TYPER.Affinity` can be expressed in routing and SGI target
+    /// values: when A3V is clear, `aff3` must be zero for every
+    /// redistributor. A mismatch usually means the device tree describes a
+    /// different GIC than the hardware, and shows up as interrupts that are
+    /// never delivered.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::AffinityMismatch`] with the first offending
+    /// redistributor affinity.
+    pub fn validate_affinity_consistency(&self) -> Result<(), GicError> {
+        if self.supports_affinity3() {
+            return Ok(());
+        }
+        for rd in self.rd_slice().iter() {
+            let affinity = unsafe { rd.as_ref() }.lpi_ref().get_affinity();
+            if affinity >> 24 != 0 {
+                return Err(GicError::AffinityMismatch(affinity));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check whether the GIC supports 1-of-N SPI routing.
+    ///
+    /// Reflects GICD_TYPER.No1N. When this returns `false`,
+    /// [`Routing::OneOfN`] cannot be used.
+    pub fn supports_1_of_n(&self) -> bool {
+        self.typer().one_of_n
+    }
+}
+
+const SPECIAL_RANGE_START: u32 = 1020;
+
+/// Saved ICC system register state of one CPU, see [`CpuInterface::save_sysreg_state`].
+///
+/// Values are raw register contents.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IccState {
+    pub ctlr: u64,
+    pub pmr: u64,
+    pub bpr0: u64,
+    pub bpr1: u64,
+    pub igrpen0: u64,
+    pub igrpen1: u64,
+    /// `ICC_AP0R<n>_EL1`
+    pub ap0r: [u64; 4],
+    /// `ICC_AP1R<n>_EL1`
+    pub ap1r: [u64; 4],
+}
+
+/// Saved GICv3 active priorities registers, see [`CpuInterface::save_active_priorities`].
+///
+/// Only the first 1, 2 or 4 registers are implemented, depending on the
+/// number of priority bits; the others are saved as zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ActivePriorities {
+    /// `ICC_AP0R<n>_EL1`
+    pub ap0r: [u64; 4],
+    /// `ICC_AP1R<n>_EL1`
+    pub ap1r: [u64; 4],
+}
+
+impl ActivePriorities {
+    /// Number of active priority levels of both groups.
+    ///
+    /// Each bit of bits `[31:0]` marks an active preemption level; the NMI bit
+    /// of ICC_AP1R0_EL1 is not counted. Interrupts that share a group priority
+    /// take one bit, so this is a lower bound on the interrupts in service.
+    pub fn active_count(&self) -> u32 {
+        self.ap0r
+            .iter()
+            .chain(&self.ap1r)
+            .map(|r| (*r as u32).count_ones())
+            .sum()
+    }
+}
+
+/// Interrupt group as seen by the CPU interface.
+///
+/// Group 1 refers to the group 1 of the current security state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Group {
+    /// Group 0, acknowledged through ICC_IAR0_EL1
+    Group0,
+    /// Group 1, acknowledged through ICC_IAR1_EL1
+    Group1,
+}
+
+/// Interrupt group of an interrupt in the distributor or redistributor,
+/// the combination of its GICD_IGROUPR and GICD_IGRPMODR bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterruptGroup {
+    /// Group 0 (IGROUPR = 0, IGRPMODR = 0)
+    Group0,
+    /// Secure Group 1 (IGROUPR = 0, IGRPMODR = 1), only with two security states
+    Group1Secure,
+    /// Non-secure Group 1 (IGROUPR = 1, IGRPMODR = 0)
+    Group1NonSecure,
+}
+
+impl InterruptGroup {
+    /// `(IGROUPR, IGRPMODR)` bit values of this group
+    pub(crate) fn bits(self) -> (bool, bool) {
+        match self {
+            InterruptGroup::Group0 => (false, false),
+            InterruptGroup::Group1Secure => (false, true),
+            InterruptGroup::Group1NonSecure => (true, false),
+        }
+    }
+
+    /// Group of the `(IGROUPR, IGRPMODR)` bit values.
+    ///
+    /// Both bits set is reserved and read as Non-secure Group 1.
+    pub(crate) fn from_bits(group1: bool, modifier: bool) -> Self {
+        match (group1, modifier) {
+            (false, false) => InterruptGroup::Group0,
+            (false, true) => InterruptGroup::Group1Secure,
+            (true, _) => InterruptGroup::Group1NonSecure,
+        }
+    }
+}
+
+/// Interrupt returned by [`CpuInterface::ack`] together with its group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AckedInterrupt {
+    pub id: IntId,
+    pub group: Group,
+}
+
+/// CPU interface capabilities from ICC_CTLR_EL1, see [`CpuInterface::cpu_capabilities`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuCaps {
+    /// Number of implemented priority bits (PRIbits + 1)
+    pub priority_bits: u8,
+    /// Number of physical INTID bits supported, 16 or 24
+    pub id_bits: u8,
+    /// Local generation of SEIs is supported
+    pub seis: bool,
+    /// Non-zero Aff3 values are supported in SGI generation (A3V)
+    pub affinity3: bool,
+    /// SGI target lists can use the range selector for Aff0 values 16-255 (RSS)
+    pub range_selector: bool,
+    /// Extended SPI and PPI INTID ranges are supported (ExtRange)
+    pub extended_range: bool,
+}
+
+/// Number of implemented ICC_AP0R<n>_EL1/ICC_AP1R<n>_EL1 registers
+fn active_priority_reg_num() -> usize {
+    match ICC_CTLR_EL1.read(ICC_CTLR_EL1::PRIBITS) + 1 {
+        0..=5 => 1,
+        6 => 2,
+        _ => 4,
+    }
+}
+
+/// Register access errors reported by GICD_STATUSR, see [`Gic::check_status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GicStatusError {
+    /// A reserved register was read (RRD)
+    pub read_reserved: bool,
+    /// A reserved register was written (WRD)
+    pub write_reserved: bool,
+    /// A write-only register was read (RWOD)
+    pub read_write_only: bool,
+    /// A read-only register was written (WROD)
+    pub write_read_only: bool,
+}
+
+impl fmt::Display for GicStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GICD_STATUSR:")?;
+        if self.read_reserved {
+            write!(f, " read of reserved register;")?;
+        }
+        if self.write_reserved {
+            write!(f, " write to reserved register;")?;
+        }
+        if self.read_write_only {
+            write!(f, " read of write-only register;")?;
+        }
+        if self.write_read_only {
+            write!(f, " write to read-only register;")?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for GicStatusError {}
+
+const EXTENDED_SPI_BASE: u32 = ESPI_RANGE.start;
+const EXTENDED_PPI_BASE: u32 = EPPI_RANGE.start;
+
+/// Decoded GICD_TYPER/GICD_TYPER2, returned by [`Gic::typer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TyperInfo {
+    /// Number of interrupt ID bits supported
+    pub id_bits: u32,
+    /// Number of implemented SPIs in the 32..1020 range
+    pub spi_count: u32,
+    /// Number of CPUs reported by CPUNumber (only meaningful without affinity routing)
+    pub cpu_num: u32,
+    /// Two security states are implemented
+    pub security_extensions: bool,
+    /// LPIs are supported
+    pub lpis: bool,
+    /// Message-based SPIs are supported
+    pub mbis: bool,
+    /// Direct virtual LPI injection is supported
+    pub dvis: bool,
+    /// Non-maskable interrupts are supported
+    pub nmi: bool,
+    /// Affinity level 3 is supported
+    pub affinity3: bool,
+    /// 1-of-N SPI routing is supported
+    pub one_of_n: bool,
+    /// SGI target lists with range selector are supported
+    pub rss: bool,
+    /// Implemented Extended SPI INTIDs
+    pub extended_spi: Option<Range<u32>>,
+    /// Implemented Extended PPI INTIDs
+    pub extended_ppi: Option<Range<u32>>,
+    /// Number of vPE ID bits, if reported
+    pub vpe_id_bits: Option<u32>,
+    /// SGIs can be configured without an active state (nASSGIreq)
+    pub nassgi_capable: bool,
+}
+
+/// Decoded GICD_CTLR, returned by [`Gic::control_state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CtlrState {
+    /// Group 0 interrupts are enabled
+    pub grp0_enabled: bool,
+    /// (Non-secure) Group 1 interrupts are enabled
+    pub grp1_enabled: bool,
+    /// Secure Group 1 interrupts are enabled
+    pub grp1_secure_enabled: bool,
+    /// Affinity routing is enabled for the Secure state
+    pub are_secure: bool,
+    /// Affinity routing is enabled for the Non-secure state
+    pub are_nonsecure: bool,
+    /// Security is disabled (single security state)
+    pub ds: bool,
+}
+
+/// Distributor group enables, see [`Gic::enabled_groups`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EnabledGroups {
+    /// Group 0 interrupts are forwarded
+    pub group0: bool,
+    /// Non-secure Group 1 interrupts (the only Group 1 with a single
+    /// security state) are forwarded
+    pub group1_nonsecure: bool,
+    /// Secure Group 1 interrupts are forwarded
+    pub group1_secure: bool,
+}
+
+/// SPI routing mode, see [`Gic::set_routing`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Routing {
+    /// Route to the PE with the given affinity.
+    Specific(Affinity),
+    /// Route to any one participating PE (1-of-N distribution).
+    ///
+    /// A PE can opt out with [`CpuInterface::set_participating`].
+    OneOfN,
+}
+
+/// Implementation parameters of a GICv3, returned by [`Gic::summary`].
+#[derive(Debug, Clone, Copy)]
+pub struct GicSummary {
+    /// Architecture revision from GICR_PIDR2 (3 = GICv3, 4 = GICv4)
+    pub arch_rev: u8,
+    /// JEP106 implementer code from GICD_IIDR
+    pub implementer: u16,
+    /// Product identifier from GICD_IIDR
+    pub product_id: u8,
+    /// Variant number from GICD_IIDR
+    pub variant: u8,
+    /// Revision number from GICD_IIDR
+    pub revision: u8,
+    /// Number of implemented SPIs
+    pub spi_count: u32,
+    /// Number of CPUs reported by GICD_TYPER.CPUNumber
+    pub max_cpu_num: usize,
+    /// Number of redistributors found in the GICR region
+    pub redistributor_count: usize,
+    /// Detected security state
+    pub security_state: SecurityState,
+    /// Whether affinity routing is enabled
+    pub affinity_routing: bool,
+    /// Whether LPIs are supported
+    pub lpis: bool,
+    /// Number of implemented priority bits
+    pub priority_bits: u8,
+}
+
+impl GicSummary {
+    /// Human readable implementer name, if known.
+    pub fn implementer_name(&self) -> Option<&'static str> {
+        match self.implementer {
+            crate::IMPLEMENTER_ARM => Some("Arm"),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for GicSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GICv{}: ", self.arch_rev)?;
+        match self.implementer_name() {
+            Some(name) => write!(f, "{name}")?,
+            None => write!(f, "implementer {:#x}", self.implementer)?,
+        }
+        write!(
+            f,
+            " product {:#x} r{}p{}, {} SPIs, {} CPUs, {} redistributors, security {:?}, ARE {}, LPIs {}, {} priority bits",
+            self.product_id,
+            self.variant,
+            self.revision,
+            self.spi_count,
+            self.max_cpu_num,
+            self.redistributor_count,
+            self.security_state,
+            if self.affinity_routing { "on" } else { "off" },
+            if self.lpis {
+                "supported"
+            } else {
+                "unsupported"
+            },
+            self.priority_bits,
+        )
+    }
+}
+
+/// Builder for configuring a single interrupt, created by [`Gic::configure`].
+///
+/// Settings that are not specified are left unchanged.
+pub struct IrqSetup<'a> {
+    gic: &'a mut Gic,
+    id: IntId,
+    priority: Option<u8>,
+    trigger: Option<Trigger>,
+    target: Option<Option<Affinity>>,
+    enable: bool,
+}
+
+impl IrqSetup<'_> {
+    /// Set the interrupt priority (0 = highest priority, 255 = lowest priority).
+    pub fn priority(mut self, priority: u8) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Set the trigger type.
+    pub fn trigger(mut self, trigger: Trigger) -> Self {
+        self.trigger = Some(trigger);
+        self
+    }
+
+    /// Route the interrupt to a specific PE. Only valid for SPIs.
+    pub fn target(mut self, affinity: Affinity) -> Self {
+        self.target = Some(Some(affinity));
+        self
+    }
+
+    /// Route the interrupt to any participating PE. Only valid for SPIs.
+    pub fn target_any(mut self) -> Self {
+        self.target = Some(None);
+        self
+    }
+
+    /// Enable the interrupt once it has been configured.
+    pub fn enable(mut self) -> Self {
+        self.enable = true;
+        self
+    }
+
+    /// Write the configuration to the GIC.
+    ///
+    /// The interrupt is disabled first, then trigger, priority and routing are
+    /// written, and finally the interrupt is enabled if [`IrqSetup::enable`]
+    /// was requested. Without `enable()` the interrupt is left disabled.
+    ///
+    /// # Errors
+    ///
+    /// - [`GicError::PrivateInterrupt`] if a target was set for an SGI or PPI.
+    /// - [`GicError::NotImplemented`] if the interrupt is not implemented,
+    ///   see [`Gic::try_set_irq_enable`]. Nothing is written in that case.
+    /// - Any error returned by [`Gic::set_target_cpu`].
+    pub fn apply(self) -> Result<(), GicError> {
+        let id = self.id;
+        if self.target.is_some() && id.is_private() {
+            return Err(GicError::PrivateInterrupt(id));
+        }
+
+        self.gic.try_set_irq_enable(id, false)?;
+
+        if let Some(trigger) = self.trigger {
+            self.gic.try_set_cfg(id, trigger)?;
+        }
+        if let Some(priority) = self.priority {
+            self.gic.try_set_priority(id, priority)?;
+        }
+        if let Some(target) = self.target {
+            self.gic.set_target_cpu(id, target)?;
+        }
+
+        if self.enable {
+            self.gic.try_set_irq_enable(id, true)?;
+        }
+        Ok(())
+    }
+}
+
+/// Defaults written to every SPI by [`Gic::init_with_config`].
+///
+/// [`Default`] matches [`Gic::init`]: priority `0xA0`, Non-secure Group 1,
+/// level-triggered.
+///
+/// [`InitConfig::priority_mask`] is applied per CPU by
+/// [`CpuInterface::init_current_cpu_with_config`].
+///
+/// # Examples
+///
+/// ```no_run
+/// # use arm_gic_driver::{VirtAddr, v3::{Gic, InitConfig, InterruptGroup}};
+/// # let mut gic = unsafe { Gic::new(VirtAddr::new(0), VirtAddr::new(0)) };
+/// // Secure firmware taking every SPI as Group 0
+/// gic.init_with_config(InitConfig {
+///     default_group: InterruptGroup::Group0,
+///     default_priority: 0x40,
+///     ..Default::default()
+/// })
+/// .unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InitConfig {
+    /// Priority of every SPI
+    pub default_priority: u8,
+    /// Group of every SPI
+    pub default_group: InterruptGroup,
+    /// Trigger mode of every SPI
+    pub default_trigger: Trigger,
+    /// Initial ICC_PMR_EL1 of each CPU interface, `0xFF` unmasks every priority
+    pub priority_mask: u8,
+}
+
+impl Default for InitConfig {
+    fn default() -> Self {
+        Self {
+            default_priority: 0xA0,
+            default_group: InterruptGroup::Group1NonSecure,
+            default_trigger: Trigger::Level,
+            priority_mask: 0xFF,
+        }
+    }
+}
+
+/// Distributor settings applied by [`Gic::init`] and [`GicBuilder::build`].
+struct InitParams {
+    security_state: Option<SecurityState>,
+    rwp_timeout: u32,
+    config: InitConfig,
+}
+
+impl Default for InitParams {
+    fn default() -> Self {
+        Self {
+            security_state: None,
+            rwp_timeout: RWP_TIMEOUT,
+            config: InitConfig::default(),
+        }
+    }
+}
+
+/// Builder that creates and initializes a [`Gic`] in one expression.
+///
+/// Settings that are not given keep the behaviour of [`Gic::init`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use arm_gic_driver::{VirtAddr, v3::{GicBuilder, SecurityState}};
+///
+/// let gic = unsafe {
+///     GicBuilder::new(VirtAddr::new(0x0800_0000), VirtAddr::new(0x080A_0000))
+/// }
+/// .security_state(SecurityState::NonSecure)
+/// .default_priority(0x80)
+/// .build()
+/// .unwrap();
+/// ```
+pub struct GicBuilder {
+    gicd: VirtAddr,
+    gicr: VirtAddr,
+    gicr_size: Option<usize>,
+    params: InitParams,
+}
+
+impl GicBuilder {
+    /// Start building a [`Gic`] for the given distributor and redistributor.
+    ///
+    /// # Safety
+    ///
+    /// Same requirements as [`Gic::new`].
+    pub unsafe fn new(gicd: VirtAddr, gicr: VirtAddr) -> Self {
+        Self {
+            gicd,
+            gicr,
+            gicr_size: None,
+            params: InitParams::default(),
+        }
+    }
+
+    /// Mapped length of the redistributor region, see [`Gic::new_with_gicr_size`].
+    pub fn gicr_size(mut self, size: usize) -> Self {
+        self.gicr_size = Some(size);
+        self
+    }
+
+    /// Use `state` instead of detecting the security state from GICD_CTLR.DS.
+    pub fn security_state(mut self, state: SecurityState) -> Self {
+        self.params.security_state = Some(state);
+        self
+    }
+
+    /// Poll GICD_CTLR.RWP at most `spins` times before failing.
+    pub fn rwp_timeout(mut self, spins: u32) -> Self {
+        self.params.rwp_timeout = spins;
+        self
+    }
+
+    /// Priority written to every SPI during initialization (default `0xA0`).
+    pub fn default_priority(mut self, priority: u8) -> Self {
+        self.params.config.default_priority = priority;
+        self
+    }
+
+    /// Group every SPI is placed in during initialization (default
+    /// Non-secure Group 1).
+    pub fn default_group(mut self, group: InterruptGroup) -> Self {
+        self.params.config.default_group = group;
+        self
     }
 
-    pub fn max_cpu_num(&self) -> usize {
-        self.gicd().max_cpu_num() as _
+    /// Trigger mode written to every SPI during initialization (default level).
+    pub fn default_trigger(mut self, trigger: Trigger) -> Self {
+        self.params.config.default_trigger = trigger;
+        self
+    }
+
+    /// Create the [`Gic`] and initialize the distributor.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Gic::init_with_config`].
+    pub fn build(self) -> Result<Gic, GicError> {
+        let mut gic = unsafe { Gic::new(self.gicd, self.gicr) };
+        gic.gicr_size = self.gicr_size;
+        gic.try_init(&self.params)?;
+        Ok(gic)
     }
 }
 
@@ -848,36 +2727,80 @@ impl Gic {
 pub struct CpuInterface {
     rd: *mut RedistributorV3,
     security_state: SecurityState,
+    /// Cached ICC_CTLR_EL1.EOImode, kept in sync by the setters of this type.
+    /// `None` until first read, as ICC_CTLR_EL1 may not be accessible before
+    /// [`Self::init_current_cpu`] enables the system register interface.
+    eoi_mode: Cell<Option<bool>>,
 }
 
 unsafe impl Send for CpuInterface {}
 
+impl fmt::Debug for CpuInterface {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CpuInterface")
+            .field("rd", &self.rd)
+            .field("security_state", &self.security_state)
+            .finish()
+    }
+}
+
 impl CpuInterface {
     fn rd(&self) -> &RedistributorV3 {
         unsafe { &*self.rd }
     }
 
+    /// Processor number of the bound redistributor (GICR_TYPER.Processor_Number).
+    ///
+    /// Unique per redistributor; also used as the target index in the
+    /// ITS target (RDbase) format when GITS_TYPER.PTA is clear.
+    pub fn processor_number(&self) -> u16 {
+        self.rd().lpi_ref().TYPER.read(gicr::TYPER::ProcessorNumber) as u16
+    }
+
+    /// Base of the bound redistributor's RD_base frame, followed by its
+    /// SGI_base frame, for registers the typed API does not model.
+    pub fn gicr_ptr(&self) -> NonNull<u8> {
+        unsafe { NonNull::new_unchecked(self.rd.cast()) }
+    }
+
+    /// Affinity of the bound redistributor (GICR_TYPER.Affinity).
+    pub fn affinity(&self) -> Affinity {
+        Affinity::from_affinity(self.rd().lpi_ref().get_affinity())
+    }
+
     /// Initialize the CPU interface for the current CPU
     ///
     /// This follows the GICv3 architecture specification for CPU interface initialization:
     /// 1. Wake up the Redistributor
     /// 2. Initialize SGI/PPI registers to known state
     /// 3. Configure CPU interface registers
-    pub fn init_current_cpu(&mut self) -> Result<(), &'static str> {
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Self::init_redistributor`].
+    pub fn init_current_cpu(&mut self) -> Result<(), GicError> {
+        self.init_current_cpu_with_config(&InitConfig::default())
+    }
+
+    /// Initialize the CPU interface for the current CPU, starting with the
+    /// priority mask of `config`
+    ///
+    /// The mask is written before the interrupt groups are enabled, so no
+    /// interrupt below it is signalled while the caller sets up its
+    /// priority scheme. Raise it later with [`Self::set_priority_mask`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::init_current_cpu`].
+    pub fn init_current_cpu_with_config(&mut self, config: &InitConfig) -> Result<(), GicError> {
         let cpu = Affinity::current();
         trace!(
             "CPU interface initialization for CPU: {:#x}",
             cpu.affinity()
         );
 
-        // 1. Wake up the Redistributor first
-        self.rd().lpi.wake()?;
-
-        // 2. Initialize SGI/PPI registers with proper sequence
-        self.rd().sgi.init_sgi_ppi(self.security_state);
-
-        // Wait for register writes to complete
-        self.rd().lpi.wait_for_rwp()?;
+        // 1-2. Wake up the Redistributor and initialize SGI/PPI registers
+        self.init_redistributor()?;
 
         // 3. Configure CPU interface system registers
         if CurrentEL.read(CurrentEL::EL) == 2 {
@@ -892,8 +2815,8 @@ impl CpuInterface {
                 .write(ICC_SRE_EL1::SRE::SET + ICC_SRE_EL1::DFB::SET + ICC_SRE_EL1::DIB::SET);
         }
 
-        // 4. Set interrupt priority mask to allow all priorities (using 8-bit priority)
-        ICC_PMR_EL1.write(ICC_PMR_EL1::PRIORITY.val(0xFF));
+        // 4. Set the initial interrupt priority mask (using 8-bit priority)
+        self.set_priority_mask(config.priority_mask);
 
         // 5. Enable appropriate interrupt groups based on security state
         match self.security_state {
@@ -920,11 +2843,99 @@ impl CpuInterface {
         if CurrentEL.read(CurrentEL::EL) == 2 {
             ICC_CTLR_EL1.modify(ICC_CTLR_EL1::EOIMODE::SET);
         }
+        self.eoi_mode.set(Some(eoi_mode()));
 
         trace!("CPU interface initialized successfully");
         Ok(())
     }
 
+    /// Wake up the bound redistributor and reset its SGI/PPI configuration
+    ///
+    /// Only touches the memory-mapped redistributor frames, so it may be used
+    /// on a handle from [`Gic::cpu_interface_by_index`] for another CPU.
+    ///
+    /// On a GIC-600 the redistributor is powered up through GICR_PWRR first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::Timeout`] if the redistributor does not power on
+    /// or wake up, or [`GicError::RwpTimeout`] if GICR_CTLR.RWP does not
+    /// clear.
+    pub fn init_redistributor(&self) -> Result<(), GicError> {
+        // GIC-600 needs the implementation defined power-up before the WAKER
+        // handshake can complete. GICR_PWRR is Secure only with two security
+        // states, where firmware has already powered the redistributor.
+        if self.security_state != SecurityState::NonSecure && self.rd().lpi.is_gic600() {
+            self.rd().lpi.power_on()?;
+        }
+        self.rd().lpi.wake()?;
+        self.rd().sgi.init_sgi_ppi(self.security_state);
+        // Wait for register writes to complete
+        self.rd().lpi.wait_for_rwp()
+    }
+
+    /// Disable the CPU interface of the current CPU, e.g. before taking it offline
+    ///
+    /// The inverse of [`Self::init_current_cpu`]:
+    /// 1. Disable Group 0 and Group 1 interrupts at the CPU interface
+    /// 2. Mask all priorities (ICC_PMR_EL1 = 0)
+    /// 3. Put the redistributor to sleep and wait for GICR_WAKER.ChildrenAsleep
+    ///
+    /// From Non-secure state with two security states, Group 0 and GICR_WAKER
+    /// belong to the secure firmware and are left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::Timeout`] if the redistributor does not report
+    /// ChildrenAsleep.
+    pub fn disable_current_cpu(&mut self) -> Result<(), GicError> {
+        let secure_access = self.security_state != SecurityState::NonSecure;
+
+        // 1. Disable interrupt groups
+        self.set_group1_enabled(false);
+        if secure_access {
+            self.set_group0_enabled(false);
+        }
+
+        // 2. Mask all priorities
+        ICC_PMR_EL1.write(ICC_PMR_EL1::PRIORITY.val(0));
+        barrier::isb(barrier::SY);
+
+        // 3. Redistributor sleep handshake
+        if secure_access {
+            self.redistributor_sleep()?;
+        }
+
+        trace!("CPU interface disabled");
+        Ok(())
+    }
+
+    /// Put the bound redistributor into its low-power state
+    ///
+    /// Sets GICR_WAKER.ProcessorSleep and waits for ChildrenAsleep. Interrupts
+    /// for this CPU are then signalled as wake requests to the power
+    /// controller instead. GICR_WAKER is Secure-only when the GIC has two
+    /// security states, so from Non-secure state this is left to firmware.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::Timeout`] if ChildrenAsleep does not become set.
+    pub fn redistributor_sleep(&self) -> Result<(), GicError> {
+        self.rd().lpi.sleep()
+    }
+
+    /// Wake up the bound redistributor
+    ///
+    /// Clears GICR_WAKER.ProcessorSleep and waits for ChildrenAsleep to clear.
+    pub fn redistributor_wake(&self) -> Result<(), GicError> {
+        self.rd().lpi.wake()
+    }
+
+    /// Check whether the bound redistributor is awake
+    pub fn is_awake(&self) -> bool {
+        self.rd().lpi.is_awake()
+    }
+
     /// Set the EOI mode for non-secure interrupts
     ///
     /// - `false` GICC_EOIR has both priority drop and deactivate interrupt functionality. Accesses to the GICC_DIR are UNPREDICTABLE.
@@ -935,20 +2946,128 @@ impl CpuInterface {
         } else {
             ICC_CTLR_EL1::EOIMODE::CLEAR
         });
+        self.eoi_mode.set(Some(is_two_step));
     }
 
     pub fn eoi_mode(&self) -> bool {
-        ICC_CTLR_EL1.is_set(ICC_CTLR_EL1::EOIMODE)
+        let mode = eoi_mode();
+        self.eoi_mode.set(Some(mode));
+        mode
+    }
+
+    /// Cached EOI mode, read from ICC_CTLR_EL1 on first use.
+    fn cached_eoi_mode(&self) -> bool {
+        self.eoi_mode.get().unwrap_or_else(|| self.eoi_mode())
     }
 
-    pub fn ack0(&self) -> IntId {
-        let raw = ICC_IAR0_EL1.read(ICC_IAR0_EL1::INTID) as u32;
-        unsafe { IntId::raw(raw) }
+    /// Acknowledge a Group 1 interrupt, run `handler` and complete it.
+    ///
+    /// Reads ICC_IAR1_EL1, calls `handler` unless the ID is special (e.g.
+    /// spurious), then writes ICC_EOIR1_EL1, followed by ICC_DIR_EL1 in two-step
+    /// EOI mode. The EOI mode is taken from a cached copy that is updated by
+    /// [`Self::init_current_cpu`], [`Self::set_eoi_mode`] and
+    /// [`Self::eoi_mode`], so ICC_CTLR_EL1 is only read on the first call
+    /// through a handle. Changing EOImode by other means requires a call to
+    /// [`Self::eoi_mode`] to refresh the cache.
+    pub fn ack_and_complete(&self, handler: impl FnOnce(IntId)) {
+        let Some(id) = self.ack1() else {
+            return;
+        };
+        handler(id);
+        self.eoi1(id);
+        if self.cached_eoi_mode() {
+            ICC_DIR_EL1.write(ICC_DIR_EL1::INTID.val(id.to_u32() as _));
+        }
+    }
+
+    /// Acknowledge and complete every pending Group 1 interrupt.
+    ///
+    /// Loops over ICC_IAR1_EL1 and ICC_EOIR1_EL1, plus ICC_DIR_EL1 in two-step
+    /// EOI mode, until a special INTID (e.g. 1023, spurious) is read. Used to
+    /// quiesce the CPU interface before switching EOI modes or handing the
+    /// GIC over, e.g. to a kexec'd kernel. No handler runs for the drained
+    /// interrupts. EOImode is read from ICC_CTLR_EL1 rather than the cache.
+    ///
+    /// `max_iterations` bounds the loop so an interrupt storm cannot keep
+    /// the CPU here forever. Returns the number of interrupts drained.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::Timeout`] if another interrupt is acknowledged after
+    /// `max_iterations` have been drained. That interrupt is completed as well
+    /// before returning.
+    pub fn flush_pending(&self, max_iterations: usize) -> Result<usize, GicError> {
+        let two_step = self.eoi_mode();
+        // One read past `max_iterations` tells an exact drain from a storm
+        for drained in 0..=max_iterations {
+            let Some(id) = self.ack1() else {
+                return Ok(drained);
+            };
+            self.eoi1(id);
+            if two_step {
+                ICC_DIR_EL1.write(ICC_DIR_EL1::INTID.val(id.to_u32() as _));
+            }
+        }
+        Err(GicError::Timeout)
+    }
+
+    /// Acknowledge a Group 0 interrupt (ICC_IAR0_EL1)
+    ///
+    /// Returns `None` if the INTID is special, e.g. 1023 (spurious).
+    pub fn ack0(&self) -> Option<IntId> {
+        non_special(ack0())
+    }
+
+    /// Acknowledge a Group 1 interrupt (ICC_IAR1_EL1)
+    ///
+    /// Returns `None` if the INTID is special, e.g. 1023 (spurious).
+    pub fn ack1(&self) -> Option<IntId> {
+        non_special(ack1())
+    }
+
+    /// Like [`Self::ack0`], but returns special INTIDs as well
+    pub fn ack0_raw(&self) -> IntId {
+        ack0()
+    }
+
+    /// Like [`Self::ack1`], but returns special INTIDs as well
+    pub fn ack1_raw(&self) -> IntId {
+        ack1()
+    }
+
+    /// Acknowledge the highest priority pending interrupt of either group
+    ///
+    /// Group 1 is tried first through ICC_IAR1_EL1. If that returns a special
+    /// INTID and Group 0 is accessible (not in Non-secure state), ICC_IAR0_EL1
+    /// is read next. The group of the returned interrupt is the one whose IAR
+    /// acknowledged it, so [`Self::eoi`] can complete it without re-reading
+    /// the group configuration.
+    pub fn ack(&self) -> AckedInterrupt {
+        let id = self.ack1_raw();
+        if id.is_special() && self.security_state != SecurityState::NonSecure {
+            return AckedInterrupt {
+                id: self.ack0_raw(),
+                group: Group::Group0,
+            };
+        }
+        AckedInterrupt {
+            id,
+            group: Group::Group1,
+        }
     }
 
-    pub fn ack1(&self) -> IntId {
-        let raw = ICC_IAR1_EL1.read(ICC_IAR1_EL1::INTID) as u32;
-        unsafe { IntId::raw(raw) }
+    /// Signal end of interrupt for an interrupt returned by [`Self::ack`]
+    ///
+    /// Writes the EOIR of the group that acknowledged it. Special INTIDs are
+    /// ignored.
+    pub fn eoi(&self, ack: AckedInterrupt) {
+        if ack.id.is_special() {
+            return;
+        }
+        match ack.group {
+            Group::Group0 => self.eoi0(ack.id),
+            Group::Group1 => self.eoi1(ack.id),
+        }
     }
 
     pub fn eoi0(&self, ack: IntId) {
@@ -960,15 +3079,282 @@ impl CpuInterface {
     }
 
     /// Deactivate an interrupt
-    pub fn dir(&self, ack: IntId) {
-        ICC_DIR_EL1.write(ICC_DIR_EL1::INTID.val(ack.to_u32() as _));
+    ///
+    /// Only valid in two-step EOI mode, see [`Self::set_eoi_mode`].
+    pub fn dir(&self, ack: IntId) -> Result<(), GicError> {
+        dir(ack)
     }
 
     /// Set the priority mask (interrupts with priority >= mask will be masked)
+    ///
+    /// Unimplemented low-order priority bits are RAZ/WI, so the hardware
+    /// truncates `mask` to the supported precision. For example with 5
+    /// priority bits, a mask of `0x01` becomes `0x00` and masks every
+    /// interrupt. Use [`Self::set_priority_mask_checked`] to see the
+    /// effective value.
     pub fn set_priority_mask(&self, mask: u8) {
         ICC_PMR_EL1.write(ICC_PMR_EL1::PRIORITY.val(mask as _));
     }
 
+    /// Set the priority mask and return the value the hardware actually holds
+    ///
+    /// If the returned value differs from `mask`, the requested mask was finer
+    /// than the implemented priority bits.
+    pub fn set_priority_mask_checked(&self, mask: u8) -> u8 {
+        self.set_priority_mask(mask);
+        self.priority_mask()
+    }
+
+    /// Get the current priority mask (ICC_PMR_EL1)
+    pub fn priority_mask(&self) -> u8 {
+        priority_mask()
+    }
+
+    /// Set whether this CPU participates in 1-of-N SPI distribution
+    ///
+    /// A core that is about to enter a low-power state can opt out so that
+    /// SPIs routed with [`Routing::OneOfN`] are delivered to other cores.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::RwpTimeout`] if GICR_CTLR.RWP does not clear.
+    pub fn set_participating(&self, participate: bool) -> Result<(), GicError> {
+        self.rd().lpi.set_participating(participate)
+    }
+
+    /// Get the current running priority
+    pub fn get_running_priority(&self) -> u8 {
+        ICC_RPR_EL1.read(ICC_RPR_EL1::PRIORITY) as u8
+    }
+
+    /// Check whether a Group 1 interrupt of `priority` would preempt the running priority
+    ///
+    /// Only the group priority bits selected by ICC_BPR1_EL1 take part in the
+    /// comparison; with BPR1 = n, bits [n-1:0] are subpriority. When
+    /// ICC_CTLR_EL1.CBPR is set, BPR1 reads as BPR0 + 1, so the common binary
+    /// point is honoured as well.
+    pub fn would_preempt(&self, priority: u8) -> bool {
+        let bpr = ICC_BPR1_EL1.read(ICC_BPR1_EL1::BINARYPOINT) as u32;
+        crate::version::would_preempt(priority, self.get_running_priority(), bpr)
+    }
+
+    /// Set the priority mask and enable it as a wakeup hint (ICC_CTLR_EL1.PMHE)
+    ///
+    /// Only interrupts with a priority higher (numerically lower) than
+    /// `priority` are signalled and can wake the core from a low-power state.
+    ///
+    /// This requires priority mask hint support. When EL3 is implemented,
+    /// ICC_CTLR_EL1.PMHE is a read-only alias of ICC_CTLR_EL3.PMHE and the hint
+    /// only takes effect if firmware has enabled it; use
+    /// [`Self::is_priority_mask_hint_enabled`] to check.
+    pub fn set_wakeup_priority(&self, priority: u8) {
+        self.set_pmhe(true);
+        ICC_PMR_EL1.write(ICC_PMR_EL1::PRIORITY.val(priority as _));
+    }
+
+    /// Enable or disable the priority mask hint (ICC_CTLR_EL1.PMHE)
+    ///
+    /// With PMHE set, the CPU interface reports ICC_PMR_EL1 to its
+    /// redistributor. The distributor may then take the mask into account for
+    /// SPIs with 1-of-N routing ([`Routing::OneOfN`]) and prefer a PE whose
+    /// mask lets the interrupt through. It may also use the mask to decide
+    /// which sleeping cores to wake, e.g. to keep masked big cores asleep on a
+    /// big.LITTLE system. With PMHE clear, the mask only filters interrupts
+    /// at this CPU interface, and a 1-of-N SPI may be sent to a PE that
+    /// cannot take it yet.
+    ///
+    /// When EL3 is implemented, ICC_CTLR_EL1.PMHE is a read-only alias of
+    /// ICC_CTLR_EL3.PMHE and the write is ignored; read it back with
+    /// [`Self::is_priority_mask_hint_enabled`].
+    pub fn set_pmhe(&self, enable: bool) {
+        ICC_CTLR_EL1.modify(if enable {
+            ICC_CTLR_EL1::PMHE::SET
+        } else {
+            ICC_CTLR_EL1::PMHE::CLEAR
+        });
+    }
+
+    /// Check whether the priority mask is used as a hint (ICC_CTLR_EL1.PMHE),
+    /// see [`Self::set_pmhe`]
+    pub fn is_priority_mask_hint_enabled(&self) -> bool {
+        ICC_CTLR_EL1.is_set(ICC_CTLR_EL1::PMHE)
+    }
+
+    /// Enable or disable signaling of `group` interrupts at this CPU interface
+    ///
+    /// Writes ICC_IGRPEN0_EL1 or ICC_IGRPEN1_EL1. This masks a whole group
+    /// without changing the priority mask.
+    pub fn set_group_enable(&self, group: Group, enable: bool) {
+        match group {
+            Group::Group0 => ICC_IGRPEN0_EL1.write(ICC_IGRPEN0_EL1::ENABLE.val(enable as _)),
+            Group::Group1 => ICC_IGRPEN1_EL1.write(ICC_IGRPEN1_EL1::ENABLE.val(enable as _)),
+        }
+        barrier::isb(barrier::SY);
+    }
+
+    /// Enable or disable Group 0 interrupts at this CPU interface
+    ///
+    /// Shorthand for [`Self::set_group_enable`] with [`Group::Group0`].
+    pub fn set_group0_enabled(&self, enable: bool) {
+        self.set_group_enable(Group::Group0, enable);
+    }
+
+    /// Enable or disable Group 1 interrupts at this CPU interface
+    ///
+    /// Shorthand for [`Self::set_group_enable`] with [`Group::Group1`], e.g.
+    /// to suppress Group 1 during a Group 0 critical section.
+    pub fn set_group1_enabled(&self, enable: bool) {
+        self.set_group_enable(Group::Group1, enable);
+    }
+
+    /// Check whether `group` interrupts are enabled at this CPU interface
+    pub fn group_enabled(&self, group: Group) -> bool {
+        match group {
+            Group::Group0 => ICC_IGRPEN0_EL1.is_set(ICC_IGRPEN0_EL1::ENABLE),
+            Group::Group1 => ICC_IGRPEN1_EL1.is_set(ICC_IGRPEN1_EL1::ENABLE),
+        }
+    }
+
+    /// Decode the read-only capability fields of ICC_CTLR_EL1 for the current CPU
+    pub fn cpu_capabilities(&self) -> CpuCaps {
+        let ctlr = ICC_CTLR_EL1.extract();
+        CpuCaps {
+            priority_bits: ctlr.read(ICC_CTLR_EL1::PRIBITS) as u8 + 1,
+            id_bits: match ctlr.read(ICC_CTLR_EL1::IDBITS) {
+                0b001 => 24,
+                _ => 16,
+            },
+            seis: ctlr.is_set(ICC_CTLR_EL1::SEIS),
+            affinity3: ctlr.is_set(ICC_CTLR_EL1::A3V),
+            range_selector: ctlr.is_set(ICC_CTLR_EL1::RSS),
+            extended_range: ctlr.is_set(ICC_CTLR_EL1::EXTRANGE),
+        }
+    }
+
+    /// Save the ICC system register state of the current CPU
+    ///
+    /// Captures ICC_PMR_EL1, ICC_BPR0/1_EL1, ICC_CTLR_EL1, ICC_IGRPEN0/1_EL1 and
+    /// the implemented active priority registers. Group 0 registers are only
+    /// accessed when the CPU interface is not in the Non-secure state.
+    ///
+    /// Must be called before entering an idle state that loses the CPU
+    /// interface context; restore with [`Self::restore_sysreg_state`].
+    pub fn save_sysreg_state(&self) -> IccState {
+        let apr = self.save_active_priorities();
+        let mut state = IccState {
+            ctlr: ICC_CTLR_EL1.get(),
+            pmr: ICC_PMR_EL1.get(),
+            bpr1: ICC_BPR1_EL1.get(),
+            igrpen1: ICC_IGRPEN1_EL1.get(),
+            ap0r: apr.ap0r,
+            ap1r: apr.ap1r,
+            ..Default::default()
+        };
+        if !matches!(self.security_state, SecurityState::NonSecure) {
+            state.bpr0 = ICC_BPR0_EL1.get();
+            state.igrpen0 = ICC_IGRPEN0_EL1.get();
+        }
+        state
+    }
+
+    /// Number of priority drops still outstanding on the current CPU
+    ///
+    /// Counts the active preemption levels in `ICC_AP0R<n>_EL1`/`ICC_AP1R<n>_EL1`,
+    /// see [`ActivePriorities::active_count`]. Each acknowledge sets a level
+    /// and the EOIR write clears it, so the count returns to zero when every
+    /// nested handler has written its EOI. In two-step EOI mode the EOI only
+    /// drops the priority, and interrupts still waiting for ICC_DIR_EL1 are
+    /// not visible here. Intended as a debug aid for unbalanced EOIs.
+    pub fn active_count(&self) -> u32 {
+        self.save_active_priorities().active_count()
+    }
+
+    /// Save the active priorities registers of the current CPU
+    ///
+    /// Only the `ICC_AP0R<n>_EL1`/`ICC_AP1R<n>_EL1` registers implemented for
+    /// the number of priority bits are read. Group 0 registers are only
+    /// accessed when the CPU interface is not in the Non-secure state.
+    ///
+    /// These must be preserved across a world switch or when a hypervisor
+    /// switches the physical CPU interface between contexts; write them back
+    /// with [`Self::restore_active_priorities`].
+    pub fn save_active_priorities(&self) -> ActivePriorities {
+        let apr_num = active_priority_reg_num();
+        let mut apr = ActivePriorities::default();
+        apr.ap1r[0] = ICC_AP1R0_EL1.get();
+        if apr_num > 1 {
+            apr.ap1r[1] = ICC_AP1R1_EL1.get();
+        }
+        if apr_num > 2 {
+            apr.ap1r[2] = ICC_AP1R2_EL1.get();
+            apr.ap1r[3] = ICC_AP1R3_EL1.get();
+        }
+
+        if !matches!(self.security_state, SecurityState::NonSecure) {
+            apr.ap0r[0] = ICC_AP0R0_EL1.get();
+            if apr_num > 1 {
+                apr.ap0r[1] = ICC_AP0R1_EL1.get();
+            }
+            if apr_num > 2 {
+                apr.ap0r[2] = ICC_AP0R2_EL1.get();
+                apr.ap0r[3] = ICC_AP0R3_EL1.get();
+            }
+        }
+        apr
+    }
+
+    /// Restore the active priorities registers saved by [`Self::save_active_priorities`]
+    pub fn restore_active_priorities(&self, apr: &ActivePriorities) {
+        let apr_num = active_priority_reg_num();
+        ICC_AP1R0_EL1.set(apr.ap1r[0]);
+        if apr_num > 1 {
+            ICC_AP1R1_EL1.set(apr.ap1r[1]);
+        }
+        if apr_num > 2 {
+            ICC_AP1R2_EL1.set(apr.ap1r[2]);
+            ICC_AP1R3_EL1.set(apr.ap1r[3]);
+        }
+
+        if !matches!(self.security_state, SecurityState::NonSecure) {
+            ICC_AP0R0_EL1.set(apr.ap0r[0]);
+            if apr_num > 1 {
+                ICC_AP0R1_EL1.set(apr.ap0r[1]);
+            }
+            if apr_num > 2 {
+                ICC_AP0R2_EL1.set(apr.ap0r[2]);
+                ICC_AP0R3_EL1.set(apr.ap0r[3]);
+            }
+        }
+        barrier::isb(barrier::SY);
+    }
+
+    /// Restore the ICC system register state saved by [`Self::save_sysreg_state`]
+    ///
+    /// The group enables are written last, so no interrupt is signalled before
+    /// the priority state is back in place.
+    pub fn restore_sysreg_state(&self, state: &IccState) {
+        let group0 = !matches!(self.security_state, SecurityState::NonSecure);
+
+        ICC_CTLR_EL1.set(state.ctlr);
+        self.eoi_mode.set(Some(eoi_mode()));
+        ICC_PMR_EL1.set(state.pmr);
+        ICC_BPR1_EL1.set(state.bpr1);
+        if group0 {
+            ICC_BPR0_EL1.set(state.bpr0);
+        }
+        // Ends with an ISB
+        self.restore_active_priorities(&ActivePriorities {
+            ap0r: state.ap0r,
+            ap1r: state.ap1r,
+        });
+
+        if group0 {
+            ICC_IGRPEN0_EL1.set(state.igrpen0);
+        }
+        ICC_IGRPEN1_EL1.set(state.igrpen1);
+        barrier::isb(barrier::SY);
+    }
+
     pub fn set_irq_enable(&self, id: IntId, enable: bool) {
         assert!(
             id.is_private(),
@@ -977,6 +3363,42 @@ impl CpuInterface {
         self.rd().sgi.set_enable_interrupt(id, enable);
     }
 
+    /// Number of PPIs of this redistributor: the 16 base PPIs plus
+    /// [`Self::extended_ppi_count`].
+    pub fn ppi_count(&self) -> u32 {
+        16 + self.extended_ppi_count()
+    }
+
+    /// Number of implemented Extended PPIs (0, 32 or 64), from
+    /// GICR_TYPER.PPInum.
+    ///
+    /// Valid Extended PPI INTIDs are `1056..1056 + count`.
+    pub fn extended_ppi_count(&self) -> u32 {
+        self.rd().lpi_ref().extended_ppi_count()
+    }
+
+    /// Enable or disable an Extended PPI (`GICR_ISENABLER<n>E`/`ICENABLER<n>E`).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::NotImplemented`] if `id` is past
+    /// [`Self::extended_ppi_count`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not in the Extended PPI range.
+    pub fn set_extended_ppi_enable(&self, id: IntId, enable: bool) -> Result<(), GicError> {
+        assert!(
+            id.classify() == IntClass::Eppi,
+            "Not an Extended PPI: {id:?}"
+        );
+        if id.to_u32() - EPPI_RANGE.start >= self.extended_ppi_count() {
+            return Err(GicError::NotImplemented(id));
+        }
+        self.rd().sgi.set_extended_enable(id, enable);
+        Ok(())
+    }
+
     pub fn is_irq_enable(&self, id: IntId) -> bool {
         assert!(
             id.is_private(),
@@ -1027,6 +3449,13 @@ impl CpuInterface {
         self.rd().sgi.set_pending(id, pending);
     }
 
+    /// Clear the pending state of an SGI or PPI in GICR_ICPENDR0.
+    ///
+    /// See [`Gic::clear_pending`] for level-sensitive interrupts.
+    pub fn clear_pending(&self, id: IntId) {
+        self.set_pending(id, false);
+    }
+
     pub fn is_pending(&self, id: IntId) -> bool {
         assert!(
             id.is_private(),
@@ -1035,6 +3464,11 @@ impl CpuInterface {
         self.rd().sgi.is_pending(id)
     }
 
+    /// Iterate over the pending SGIs and PPIs of this CPU without acknowledging them.
+    pub fn pending_iter(&self) -> impl Iterator<Item = IntId> + '_ {
+        iter_irq_bits(self.rd().sgi.ISPENDR0.get(), 0)
+    }
+
     pub fn set_cfg(&self, id: IntId, cfg: Trigger) {
         assert!(
             id.is_private(),
@@ -1051,16 +3485,29 @@ impl CpuInterface {
         self.rd().sgi.get_cfgr(id)
     }
 
-    pub fn send_sgi(&self, sgi_id: IntId, target: SGITarget) {
-        send_sgi(sgi_id, target);
+    pub fn send_sgi(&self, sgi_id: IntId, target: SGITarget) -> Result<(), GicError> {
+        send_sgi(sgi_id, target)
     }
 
     pub const fn trap_operations(&self) -> TrapOp {
-        TrapOp {}
+        TrapOp {
+            group0: !matches!(self.security_state, SecurityState::NonSecure),
+        }
     }
 }
 
-pub struct TrapOp {}
+/// Interrupt handling operations usable from a trap handler without a
+/// [`CpuInterface`].
+///
+/// # Group 0
+///
+/// Group 0 interrupts are normally signalled as FIQ, not IRQ; this driver does
+/// not change that routing, so [`Self::ack_group0`] belongs in the FIQ
+/// handler. Group 0 registers are only accessible when the CPU interface was
+/// created in Secure or single security state; see [`Self::handles_group0`].
+pub struct TrapOp {
+    group0: bool,
+}
 
 unsafe impl Send for TrapOp {}
 unsafe impl Sync for TrapOp {}
@@ -1070,11 +3517,37 @@ impl TrapOp {
         eoi_mode()
     }
 
-    pub fn ack0(&self) -> IntId {
+    /// Get the current priority mask (ICC_PMR_EL1)
+    pub fn priority_mask(&self) -> u8 {
+        priority_mask()
+    }
+
+    /// Get the current running priority (ICC_RPR_EL1)
+    pub fn running_priority(&self) -> u8 {
+        running_priority()
+    }
+
+    /// Acknowledge a Group 0 interrupt (ICC_IAR0_EL1)
+    ///
+    /// Returns `None` if the INTID is special, e.g. 1023 (spurious).
+    pub fn ack0(&self) -> Option<IntId> {
+        non_special(ack0())
+    }
+
+    /// Acknowledge a Group 1 interrupt (ICC_IAR1_EL1)
+    ///
+    /// Returns `None` if the INTID is special, e.g. 1023 (spurious).
+    pub fn ack1(&self) -> Option<IntId> {
+        non_special(ack1())
+    }
+
+    /// Like [`Self::ack0`], but returns special INTIDs as well
+    pub fn ack0_raw(&self) -> IntId {
         ack0()
     }
 
-    pub fn ack1(&self) -> IntId {
+    /// Like [`Self::ack1`], but returns special INTIDs as well
+    pub fn ack1_raw(&self) -> IntId {
         ack1()
     }
 
@@ -1082,13 +3555,93 @@ impl TrapOp {
         eoi0(ack);
     }
 
+    /// Whether this handle may service Group 0 interrupts
+    pub fn handles_group0(&self) -> bool {
+        self.group0
+    }
+
+    /// Acknowledge a Group 0 interrupt (ICC_IAR0_EL1), typically from the FIQ handler
+    ///
+    /// Returns `None` if the INTID is special, e.g. 1023 (spurious), like
+    /// [`Self::ack0`]. Complete it with [`Self::eoi_group0`] and, in two-step
+    /// EOI mode, [`Self::dir`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if Group 0 is not accessible, see [`Self::handles_group0`].
+    pub fn ack_group0(&self) -> Option<IntId> {
+        assert!(
+            self.group0,
+            "Group 0 is not accessible from Non-secure state"
+        );
+        self.ack0()
+    }
+
+    /// Signal end of interrupt for an interrupt from [`Self::ack_group0`]
+    ///
+    /// # Panics
+    ///
+    /// Panics if Group 0 is not accessible, see [`Self::handles_group0`].
+    pub fn eoi_group0(&self, ack: IntId) {
+        assert!(
+            self.group0,
+            "Group 0 is not accessible from Non-secure state"
+        );
+        eoi0(ack);
+    }
+
+    /// Get the Group 0 binary point (ICC_BPR0_EL1)
+    pub fn group0_binary_point(&self) -> u8 {
+        ICC_BPR0_EL1.read(ICC_BPR0_EL1::BINARYPOINT) as u8
+    }
+
     pub fn eoi1(&self, ack: IntId) {
         eoi1(ack);
     }
 
     /// Deactivate an interrupt
-    pub fn dir(&self, ack: IntId) {
-        dir(ack);
+    ///
+    /// Only valid in two-step EOI mode, see [`Self::eoi_mode`].
+    pub fn dir(&self, ack: IntId) -> Result<(), GicError> {
+        dir(ack)
+    }
+
+    /// Service every pending Group 1 interrupt with `handler`.
+    ///
+    /// Acknowledges through ICC_IAR1_EL1, calls `handler` with the INTID,
+    /// then completes it with ICC_EOIR1_EL1 and, in two-step EOI mode,
+    /// ICC_DIR_EL1. This repeats until a special INTID (e.g. 1023, spurious)
+    /// is read. `handler` returns whether it handled the interrupt; an
+    /// unhandled interrupt is logged and still completed so that it does not
+    /// stay active.
+    ///
+    /// Returns the number of interrupts acknowledged.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use arm_gic_driver::v3::*;
+    /// # let gic = unsafe { Gic::new(VirtAddr::new(0), VirtAddr::new(0)) };
+    /// let trap = gic.cpu_interface().trap_operations();
+    /// // In the IRQ handler
+    /// let serviced = trap.dispatch(|id| id == IntId::ppi(14));
+    /// ```
+    pub fn dispatch<F: FnMut(IntId) -> bool>(&self, mut handler: F) -> usize {
+        let two_step = eoi_mode();
+        let mut serviced = 0;
+        loop {
+            let Some(id) = non_special(ack1()) else {
+                return serviced;
+            };
+            if !handler(id) {
+                warn!("Unhandled interrupt: {id:?}");
+            }
+            eoi1(id);
+            if two_step {
+                ICC_DIR_EL1.write(ICC_DIR_EL1::INTID.val(id.to_u32() as _));
+            }
+            serviced += 1;
+        }
     }
 }
 
@@ -1096,14 +3649,35 @@ pub fn eoi_mode() -> bool {
     ICC_CTLR_EL1.is_set(ICC_CTLR_EL1::EOIMODE)
 }
 
+pub fn priority_mask() -> u8 {
+    ICC_PMR_EL1.read(ICC_PMR_EL1::PRIORITY) as u8
+}
+
+pub fn running_priority() -> u8 {
+    ICC_RPR_EL1.read(ICC_RPR_EL1::PRIORITY) as u8
+}
+
+/// Decode the INTID of an ICC_IAR0_EL1 / ICC_IAR1_EL1 value.
+///
+/// The INTID is the full 24-bit field and is not remapped, so an Extended
+/// SPI or PPI comes back in [`ESPI_RANGE`] / [`EPPI_RANGE`] and
+/// [`IntId::classify`] and [`IntId::logical_number`] recover its number.
+pub(crate) fn intid_from_iar(iar: u64) -> IntId {
+    let raw = LocalRegisterCopy::<u64, ICC_IAR1_EL1::Register>::new(iar).read(ICC_IAR1_EL1::INTID);
+    unsafe { IntId::raw(raw as u32) }
+}
+
+/// `None` for special INTIDs (1020-1023), which are not interrupts to handle.
+fn non_special(id: IntId) -> Option<IntId> {
+    (!id.is_special()).then_some(id)
+}
+
 pub fn ack0() -> IntId {
-    let raw = ICC_IAR0_EL1.read(ICC_IAR0_EL1::INTID) as u32;
-    unsafe { IntId::raw(raw) }
+    intid_from_iar(ICC_IAR0_EL1.get())
 }
 
 pub fn ack1() -> IntId {
-    let raw = ICC_IAR1_EL1.read(ICC_IAR1_EL1::INTID) as u32;
-    unsafe { IntId::raw(raw) }
+    intid_from_iar(ICC_IAR1_EL1.get())
 }
 
 pub fn eoi0(ack: IntId) {
@@ -1115,8 +3689,16 @@ pub fn eoi1(ack: IntId) {
 }
 
 /// Deactivate an interrupt
-pub fn dir(ack: IntId) {
+///
+/// Writes to ICC_DIR_EL1 are UNPREDICTABLE in single-step EOI mode, so this
+/// returns [`GicError::NotTwoStepEoiMode`] without touching the register
+/// unless ICC_CTLR_EL1.EOImode is set.
+pub fn dir(ack: IntId) -> Result<(), GicError> {
+    if !eoi_mode() {
+        return Err(GicError::NotTwoStepEoiMode);
+    }
     ICC_DIR_EL1.write(ICC_DIR_EL1::INTID.val(ack.to_u32() as _));
+    Ok(())
 }
 
 /// Send a Software Generated Interrupt (SGI) to target CPUs.
@@ -1129,6 +3711,27 @@ pub fn dir(ack: IntId) {
 /// * `sgi_id` - SGI interrupt ID (0-15)
 /// * `target` - Target specification for the SGI
 ///
+/// # Errors
+///
+/// Returns [`GicError::Affinity3NotSupported`] if the target list uses a non-zero
+/// `aff3` but the CPU interface does not support affinity level 3 (ICC_CTLR_EL1.A3V).
+///
+/// Returns [`GicError::RangeSelectorNotSupported`] if the target list contains an
+/// affinity level 0 value of 16 or above but the CPU interface does not support
+/// the range selector (ICC_CTLR_EL1.RSS). Nothing is sent in either case.
+///
+/// # Ordering
+///
+/// A `DSB ISHST` is issued before the first ICC_SGI1R_EL1 write, so memory
+/// writes made before calling this function are visible to the target CPUs
+/// before the SGI arrives. This makes it safe to pass a message through
+/// shared memory and then signal it with an SGI.
+///
+/// SGIs are not queued: sending the same SGI ID to the same target again
+/// before the target has acknowledged it leaves it pending once, so it is
+/// delivered only once. Different SGI IDs are delivered in priority order,
+/// not in the order they were sent.
+///
 /// # Example
 ///
 /// ```ignore
@@ -1137,27 +3740,47 @@ pub fn dir(ack: IntId) {
 ///
 /// // Send SGI 5 to all other CPUs
 /// let sgi_id = IntId::sgi(5);
-/// arm_gic_driver::v3::send_sgi(sgi_id, SGITarget::AllOther);
+/// arm_gic_driver::v3::send_sgi(sgi_id, SGITarget::AllOther).unwrap();
 /// ```
-pub fn send_sgi(sgi_id: IntId, target: SGITarget) {
+pub fn send_sgi(sgi_id: IntId, target: SGITarget) -> Result<(), GicError> {
     assert!(sgi_id.is_sgi(), "Invalid SGI ID: {sgi_id:?}");
 
     let sgi_num = sgi_id.to_u32();
 
+    // Make prior memory writes visible to the targets before the SGI
+    barrier::dsb(barrier::ISHST);
+
     match target {
         SGITarget::All => {
             trace!("Sending SGI {sgi_num} to all CPUs");
             ICC_SGI1R_EL1.write(ICC_SGI1R_EL1::INTID.val(sgi_num as u64) + ICC_SGI1R_EL1::IRM::SET);
         }
         SGITarget::List(val) => {
+            if val.aff3 != 0 && !ICC_CTLR_EL1.is_set(ICC_CTLR_EL1::A3V) {
+                return Err(GicError::Affinity3NotSupported);
+            }
+            if val.target_list[1..].iter().any(|&l| l != 0)
+                && !ICC_CTLR_EL1.is_set(ICC_CTLR_EL1::RSS)
+            {
+                return Err(GicError::RangeSelectorNotSupported);
+            }
             trace!("Sending SGI {sgi_num} to CPUs with affinity: {val:#x?}");
-            // Send to specific CPUs identified by affinity and target list
-            let value = ICC_SGI1R_EL1::INTID.val(sgi_num as u64)
-                + ICC_SGI1R_EL1::AFF3.val(val.aff3 as u64)
-                + ICC_SGI1R_EL1::AFF2.val(val.aff2 as u64)
-                + ICC_SGI1R_EL1::AFF1.val(val.aff1 as u64)
-                + ICC_SGI1R_EL1::TARGETLIST.val(val.target_list as u64);
-            ICC_SGI1R_EL1.write(value);
+            // Send to specific CPUs identified by affinity and target list,
+            // one write per 16-CPU range selected by RS
+            for (rs, &list) in val.target_list.iter().enumerate() {
+                if list == 0 {
+                    continue;
+                }
+                let value = ICC_SGI1R_EL1::INTID.val(sgi_num as u64)
+                    + ICC_SGI1R_EL1::AFF3.val(val.aff3 as u64)
+                    + ICC_SGI1R_EL1::AFF2.val(val.aff2 as u64)
+                    + ICC_SGI1R_EL1::AFF1.val(val.aff1 as u64)
+                    + ICC_SGI1R_EL1::RS.val(rs as u64)
+                    + ICC_SGI1R_EL1::TARGETLIST.val(list as u64);
+                ICC_SGI1R_EL1.write(value);
+            }
         }
     }
+    barrier::isb(barrier::SY);
+    Ok(())
 }
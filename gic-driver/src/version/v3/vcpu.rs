@@ -0,0 +1,511 @@
+use aarch64_cpu::asm::barrier;
+use tock_registers::{LocalRegisterCopy, interfaces::*};
+
+use super::Group;
+use crate::{GicError, IntId, sys_reg::*};
+
+/// GICv3 virtual CPU interface control (ICH_*_EL2), the GICv3 analogue of
+/// the GICv2 `HypervisorInterface`.
+///
+/// All state lives in the system registers of the executing PE, so every
+/// method acts on the current CPU and must run at EL2. Obtain one with
+/// [`Gic::virtual_cpu_interface`](super::Gic::virtual_cpu_interface).
+#[derive(Debug)]
+pub struct VirtualCpuInterface {
+    _priv: (),
+}
+
+impl VirtualCpuInterface {
+    pub(crate) fn new() -> Self {
+        Self { _priv: () }
+    }
+
+    /// Reset the virtual CPU interface of the current CPU
+    ///
+    /// Disables the interface, clears every implemented list register and
+    /// the virtual active priorities.
+    pub fn init_current_cpu(&mut self) {
+        ICH_HCR_EL2.set(0);
+        for lr in 0..self.list_register_count() {
+            ich_lr_el2_set(lr, LocalRegisterCopy::new(0));
+        }
+        self.restore_active_priorities(&VirtualActivePriorities::default());
+        barrier::isb(barrier::SY);
+    }
+
+    /// Enable the virtual CPU interface (ICH_HCR_EL2.En)
+    pub fn enable(&self) {
+        ICH_HCR_EL2.modify(ICH_HCR_EL2::EN::SET);
+        barrier::isb(barrier::SY);
+    }
+
+    /// Disable the virtual CPU interface
+    pub fn disable(&self) {
+        ICH_HCR_EL2.modify(ICH_HCR_EL2::EN::CLEAR);
+        barrier::isb(barrier::SY);
+    }
+
+    /// Check whether the virtual CPU interface is enabled
+    pub fn is_enabled(&self) -> bool {
+        ICH_HCR_EL2.is_set(ICH_HCR_EL2::EN)
+    }
+
+    /// Enable/disable underflow maintenance interrupt
+    pub fn set_underflow_interrupt(&self, enable: bool) {
+        if enable {
+            ICH_HCR_EL2.modify(ICH_HCR_EL2::UIE::SET);
+        } else {
+            ICH_HCR_EL2.modify(ICH_HCR_EL2::UIE::CLEAR);
+        }
+    }
+
+    /// Enable/disable list register entry not present maintenance interrupt
+    pub fn set_list_reg_entry_not_present_interrupt(&self, enable: bool) {
+        if enable {
+            ICH_HCR_EL2.modify(ICH_HCR_EL2::LRENPIE::SET);
+        } else {
+            ICH_HCR_EL2.modify(ICH_HCR_EL2::LRENPIE::CLEAR);
+        }
+    }
+
+    /// Enable/disable no pending maintenance interrupt
+    pub fn set_no_pending_interrupt(&self, enable: bool) {
+        if enable {
+            ICH_HCR_EL2.modify(ICH_HCR_EL2::NPIE::SET);
+        } else {
+            ICH_HCR_EL2.modify(ICH_HCR_EL2::NPIE::CLEAR);
+        }
+    }
+
+    /// Enable/disable virtual Group 0 enable maintenance interrupt
+    pub fn set_vgrp0_enable_interrupt(&self, enable: bool) {
+        if enable {
+            ICH_HCR_EL2.modify(ICH_HCR_EL2::VGRP0EIE::SET);
+        } else {
+            ICH_HCR_EL2.modify(ICH_HCR_EL2::VGRP0EIE::CLEAR);
+        }
+    }
+
+    /// Enable/disable virtual Group 0 disable maintenance interrupt
+    pub fn set_vgrp0_disable_interrupt(&self, enable: bool) {
+        if enable {
+            ICH_HCR_EL2.modify(ICH_HCR_EL2::VGRP0DIE::SET);
+        } else {
+            ICH_HCR_EL2.modify(ICH_HCR_EL2::VGRP0DIE::CLEAR);
+        }
+    }
+
+    /// Enable/disable virtual Group 1 enable maintenance interrupt
+    pub fn set_vgrp1_enable_interrupt(&self, enable: bool) {
+        if enable {
+            ICH_HCR_EL2.modify(ICH_HCR_EL2::VGRP1EIE::SET);
+        } else {
+            ICH_HCR_EL2.modify(ICH_HCR_EL2::VGRP1EIE::CLEAR);
+        }
+    }
+
+    /// Enable/disable virtual Group 1 disable maintenance interrupt
+    pub fn set_vgrp1_disable_interrupt(&self, enable: bool) {
+        if enable {
+            ICH_HCR_EL2.modify(ICH_HCR_EL2::VGRP1DIE::SET);
+        } else {
+            ICH_HCR_EL2.modify(ICH_HCR_EL2::VGRP1DIE::CLEAR);
+        }
+    }
+
+    /// Get the number of implemented list registers (ICH_VTR_EL2.ListRegs + 1)
+    pub fn list_register_count(&self) -> usize {
+        ich_lr_count()
+    }
+
+    /// Get the number of virtual priority bits implemented (ICH_VTR_EL2.PRIbits + 1)
+    pub fn virtual_priority_bits(&self) -> u32 {
+        ICH_VTR_EL2.read(ICH_VTR_EL2::PRIBITS) as u32 + 1
+    }
+
+    /// Write a virtual interrupt to a list register
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lr_index` is not below [`Self::list_register_count`].
+    pub fn set_virtual_interrupt(&self, lr_index: usize, config: VirtualInterruptConfig) {
+        assert!(
+            lr_index < self.list_register_count(),
+            "Invalid list register index: {lr_index}"
+        );
+        ich_lr_el2_set(lr_index, config.encode());
+    }
+
+    /// Read the virtual interrupt held in a list register
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lr_index` is not below [`Self::list_register_count`].
+    pub fn get_virtual_interrupt(&self, lr_index: usize) -> VirtualInterruptConfig {
+        assert!(
+            lr_index < self.list_register_count(),
+            "Invalid list register index: {lr_index}"
+        );
+        VirtualInterruptConfig::decode(ich_lr_el2_get(lr_index))
+    }
+
+    /// Inject a hardware interrupt into the guest through a free list register
+    ///
+    /// The entry is written pending with `HW` set, so the guest's deactivation
+    /// of `vintid` also deactivates the physical interrupt `pintid`. Returns
+    /// the index of the list register used.
+    ///
+    /// # Errors
+    ///
+    /// - [`GicError::PhysicalIdOutOfRange`] if `pintid` does not fit the
+    ///   16-bit pINTID field.
+    /// - [`GicError::ListRegistersFull`] if no list register is free.
+    pub fn inject_hw(
+        &self,
+        vintid: IntId,
+        pintid: IntId,
+        priority: u8,
+        group: Group,
+    ) -> Result<usize, GicError> {
+        if pintid.to_u32() > u16::MAX as u32 {
+            return Err(GicError::PhysicalIdOutOfRange(pintid));
+        }
+        self.inject(VirtualInterruptConfig {
+            virtual_id: vintid,
+            priority,
+            state: VirtualInterruptState::Pending,
+            group,
+            interrupt_type: VirtualInterruptType::Hardware {
+                physical_id: pintid.to_u32(),
+            },
+        })
+    }
+
+    /// Inject a purely virtual interrupt into the guest through a free list register
+    ///
+    /// With `eoi_maintenance` set, a maintenance interrupt is raised when the
+    /// guest deactivates the interrupt. Returns the index of the list
+    /// register used.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GicError::ListRegistersFull`] if no list register is free.
+    pub fn inject_sw(
+        &self,
+        vintid: IntId,
+        priority: u8,
+        group: Group,
+        eoi_maintenance: bool,
+    ) -> Result<usize, GicError> {
+        self.inject(VirtualInterruptConfig {
+            virtual_id: vintid,
+            priority,
+            state: VirtualInterruptState::Pending,
+            group,
+            interrupt_type: VirtualInterruptType::Software { eoi_maintenance },
+        })
+    }
+
+    fn inject(&self, config: VirtualInterruptConfig) -> Result<usize, GicError> {
+        let lr = self
+            .free_list_register()
+            .ok_or(GicError::ListRegistersFull)?;
+        ich_lr_el2_set(lr, config.encode());
+        Ok(lr)
+    }
+
+    /// Find the lowest list register that holds no valid interrupt (ICH_ELRSR_EL2)
+    pub fn free_list_register(&self) -> Option<usize> {
+        let empty = self.get_empty_lr_status();
+        (0..self.list_register_count()).find(|&lr| empty & (1 << lr) != 0)
+    }
+
+    /// Check if a list register is empty (invalid state)
+    ///
+    /// Indices past the implemented list registers are reported as not empty.
+    pub fn is_list_register_empty(&self, lr_index: usize) -> bool {
+        lr_index < self.list_register_count() && self.get_empty_lr_status() & (1 << lr_index) != 0
+    }
+
+    /// Clear a list register (set to invalid state)
+    pub fn clear_list_register(&self, lr_index: usize) {
+        assert!(
+            lr_index < self.list_register_count(),
+            "Invalid list register index: {lr_index}"
+        );
+        ich_lr_el2_set(lr_index, LocalRegisterCopy::new(0));
+    }
+
+    /// Get the maintenance interrupt status (ICH_MISR_EL2)
+    pub fn get_maintenance_status(&self) -> u64 {
+        ICH_MISR_EL2.get()
+    }
+
+    /// Get the list registers with an EOI maintenance interrupt (ICH_EISR_EL2)
+    pub fn get_eoi_status(&self) -> u16 {
+        ICH_EISR_EL2.read(ICH_EISR_EL2::STATUS) as u16
+    }
+
+    /// Get the list registers that hold no valid interrupt (ICH_ELRSR_EL2)
+    pub fn get_empty_lr_status(&self) -> u16 {
+        ICH_ELRSR_EL2.read(ICH_ELRSR_EL2::STATUS) as u16
+    }
+
+    /// Decode the pending maintenance interrupt causes (ICH_MISR_EL2)
+    ///
+    /// For an EOI maintenance interrupt, the list registers that signalled
+    /// EOI are taken from ICH_EISR_EL2 so they can be recycled.
+    pub fn handle_maintenance(&self) -> MaintenanceEvent {
+        let misr = ICH_MISR_EL2.extract();
+        let eoi = misr.is_set(ICH_MISR_EL2::EOI);
+        MaintenanceEvent {
+            eoi,
+            underflow: misr.is_set(ICH_MISR_EL2::U),
+            lr_entry_not_present: misr.is_set(ICH_MISR_EL2::LRENP),
+            no_pending: misr.is_set(ICH_MISR_EL2::NP),
+            vgrp0_enabled: misr.is_set(ICH_MISR_EL2::VGRP0E),
+            vgrp0_disabled: misr.is_set(ICH_MISR_EL2::VGRP0D),
+            vgrp1_enabled: misr.is_set(ICH_MISR_EL2::VGRP1E),
+            vgrp1_disabled: misr.is_set(ICH_MISR_EL2::VGRP1D),
+            eoi_lrs: if eoi { self.get_eoi_status() } else { 0 },
+        }
+    }
+
+    /// Write the virtual machine control register (ICH_VMCR_EL2) of the current vCPU
+    pub fn set_vmcr(&self, vmcr: VmcrConfig) {
+        let mut val = ICH_VMCR_EL2::VBPR0.val(vmcr.bpr0 as u64)
+            + ICH_VMCR_EL2::VBPR1.val(vmcr.bpr1 as u64)
+            + ICH_VMCR_EL2::VPMR.val(vmcr.priority_mask as u64);
+        if vmcr.grp0_enable {
+            val += ICH_VMCR_EL2::VENG0::SET;
+        }
+        if vmcr.grp1_enable {
+            val += ICH_VMCR_EL2::VENG1::SET;
+        }
+        if vmcr.ack_ctl {
+            val += ICH_VMCR_EL2::VACKCTL::SET;
+        }
+        if vmcr.fiq_enable {
+            val += ICH_VMCR_EL2::VFIQEN::SET;
+        }
+        if vmcr.cbpr {
+            val += ICH_VMCR_EL2::VCBPR::SET;
+        }
+        if vmcr.eoi_mode {
+            val += ICH_VMCR_EL2::VEOIM::SET;
+        }
+        ICH_VMCR_EL2.write(val);
+    }
+
+    /// Read the virtual machine control register (ICH_VMCR_EL2) of the current vCPU
+    pub fn get_vmcr(&self) -> VmcrConfig {
+        let vmcr = ICH_VMCR_EL2.extract();
+        VmcrConfig {
+            grp0_enable: vmcr.is_set(ICH_VMCR_EL2::VENG0),
+            grp1_enable: vmcr.is_set(ICH_VMCR_EL2::VENG1),
+            ack_ctl: vmcr.is_set(ICH_VMCR_EL2::VACKCTL),
+            fiq_enable: vmcr.is_set(ICH_VMCR_EL2::VFIQEN),
+            cbpr: vmcr.is_set(ICH_VMCR_EL2::VCBPR),
+            eoi_mode: vmcr.is_set(ICH_VMCR_EL2::VEOIM),
+            bpr0: vmcr.read(ICH_VMCR_EL2::VBPR0) as u8,
+            bpr1: vmcr.read(ICH_VMCR_EL2::VBPR1) as u8,
+            priority_mask: vmcr.read(ICH_VMCR_EL2::VPMR) as u8,
+        }
+    }
+
+    /// Save the virtual active priorities (`ICH_AP0R<n>_EL2`/`ICH_AP1R<n>_EL2`)
+    ///
+    /// Must be saved together with the list registers when switching vCPUs.
+    pub fn save_active_priorities(&self) -> VirtualActivePriorities {
+        let apr_num = self.active_priority_reg_num();
+        let mut apr = VirtualActivePriorities::default();
+        apr.ap0r[0] = ICH_AP0R0_EL2.get();
+        apr.ap1r[0] = ICH_AP1R0_EL2.get();
+        if apr_num > 1 {
+            apr.ap0r[1] = ICH_AP0R1_EL2.get();
+            apr.ap1r[1] = ICH_AP1R1_EL2.get();
+        }
+        if apr_num > 2 {
+            apr.ap0r[2] = ICH_AP0R2_EL2.get();
+            apr.ap0r[3] = ICH_AP0R3_EL2.get();
+            apr.ap1r[2] = ICH_AP1R2_EL2.get();
+            apr.ap1r[3] = ICH_AP1R3_EL2.get();
+        }
+        apr
+    }
+
+    /// Restore the virtual active priorities saved by [`Self::save_active_priorities`]
+    pub fn restore_active_priorities(&self, apr: &VirtualActivePriorities) {
+        let apr_num = self.active_priority_reg_num();
+        ICH_AP0R0_EL2.set(apr.ap0r[0]);
+        ICH_AP1R0_EL2.set(apr.ap1r[0]);
+        if apr_num > 1 {
+            ICH_AP0R1_EL2.set(apr.ap0r[1]);
+            ICH_AP1R1_EL2.set(apr.ap1r[1]);
+        }
+        if apr_num > 2 {
+            ICH_AP0R2_EL2.set(apr.ap0r[2]);
+            ICH_AP0R3_EL2.set(apr.ap0r[3]);
+            ICH_AP1R2_EL2.set(apr.ap1r[2]);
+            ICH_AP1R3_EL2.set(apr.ap1r[3]);
+        }
+    }
+
+    /// Number of implemented ICH_AP0R<n>_EL2/ICH_AP1R<n>_EL2 registers,
+    /// from the preemption bits (ICH_VTR_EL2.PREbits + 1)
+    fn active_priority_reg_num(&self) -> usize {
+        match ICH_VTR_EL2.read(ICH_VTR_EL2::PREBITS) + 1 {
+            0..=5 => 1,
+            6 => 2,
+            _ => 4,
+        }
+    }
+}
+
+/// Saved virtual active priorities, see [`VirtualCpuInterface::save_active_priorities`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VirtualActivePriorities {
+    /// `ICH_AP0R<n>_EL2`
+    pub ap0r: [u64; 4],
+    /// `ICH_AP1R<n>_EL2`
+    pub ap1r: [u64; 4],
+}
+
+/// Maintenance interrupt causes, see [`VirtualCpuInterface::handle_maintenance`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MaintenanceEvent {
+    /// A list register with EOI set was deactivated by the guest
+    pub eoi: bool,
+    /// Zero or one list register holds a valid interrupt (underflow)
+    pub underflow: bool,
+    /// The guest deactivated an interrupt that is not in a list register
+    pub lr_entry_not_present: bool,
+    /// No list register is in the pending state
+    pub no_pending: bool,
+    /// The guest enabled virtual Group 0
+    pub vgrp0_enabled: bool,
+    /// The guest disabled virtual Group 0
+    pub vgrp0_disabled: bool,
+    /// The guest enabled virtual Group 1
+    pub vgrp1_enabled: bool,
+    /// The guest disabled virtual Group 1
+    pub vgrp1_disabled: bool,
+    /// Bitmap of the list registers that signalled EOI (ICH_EISR_EL2)
+    pub eoi_lrs: u16,
+}
+
+impl MaintenanceEvent {
+    /// Indices of the list registers that signalled EOI
+    pub fn eoi_list_registers(&self) -> impl Iterator<Item = usize> + use<> {
+        let lrs = self.eoi_lrs;
+        (0..16).filter(move |lr| lrs & (1 << lr) != 0)
+    }
+}
+
+/// Decoded ICH_VMCR_EL2, the vCPU's view of its virtual CPU interface control state.
+///
+/// Save with [`VirtualCpuInterface::get_vmcr`] and restore with
+/// [`VirtualCpuInterface::set_vmcr`] on vCPU context switches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VmcrConfig {
+    /// Virtual Group 0 interrupts enabled (ICC_IGRPEN0_EL1 as seen by the guest)
+    pub grp0_enable: bool,
+    /// Virtual Group 1 interrupts enabled (ICC_IGRPEN1_EL1 as seen by the guest)
+    pub grp1_enable: bool,
+    /// Legacy AckCtl, RES0 when system register access is used
+    pub ack_ctl: bool,
+    /// Group 0 virtual interrupts signalled as vFIQ
+    pub fiq_enable: bool,
+    /// Common binary point (ICC_CTLR_EL1.CBPR)
+    pub cbpr: bool,
+    /// Two-step EOI mode (ICC_CTLR_EL1.EOImode)
+    pub eoi_mode: bool,
+    /// Group 0 binary point (ICC_BPR0_EL1)
+    pub bpr0: u8,
+    /// Group 1 binary point (ICC_BPR1_EL1)
+    pub bpr1: u8,
+    /// 8-bit virtual priority mask (ICC_PMR_EL1)
+    pub priority_mask: u8,
+}
+
+/// Virtual interrupt held in a list register (`ICH_LR<n>_EL2`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VirtualInterruptConfig {
+    pub virtual_id: IntId,
+    pub priority: u8,
+    pub state: VirtualInterruptState,
+    pub group: Group,
+    pub interrupt_type: VirtualInterruptType,
+}
+
+impl VirtualInterruptConfig {
+    fn encode(&self) -> LocalRegisterCopy<u64, ICH_LR_EL2::Register> {
+        let mut lr = LocalRegisterCopy::new(0);
+        let mut val = ICH_LR_EL2::VINTID.val(self.virtual_id.to_u32() as u64)
+            + ICH_LR_EL2::PRIORITY.val(self.priority as u64)
+            + ICH_LR_EL2::STATE.val(self.state as u64);
+        if self.group == Group::Group1 {
+            val += ICH_LR_EL2::GROUP::SET;
+        }
+        match self.interrupt_type {
+            VirtualInterruptType::Hardware { physical_id } => {
+                val += ICH_LR_EL2::HW::SET + ICH_LR_EL2::PINTID.val(physical_id as u64);
+            }
+            VirtualInterruptType::Software { eoi_maintenance } => {
+                if eoi_maintenance {
+                    val += ICH_LR_EL2::EOI::SET;
+                }
+            }
+        }
+        lr.write(val);
+        lr
+    }
+
+    fn decode(lr: LocalRegisterCopy<u64, ICH_LR_EL2::Register>) -> Self {
+        let state = match lr.read(ICH_LR_EL2::STATE) {
+            1 => VirtualInterruptState::Pending,
+            2 => VirtualInterruptState::Active,
+            3 => VirtualInterruptState::PendingAndActive,
+            _ => VirtualInterruptState::Invalid,
+        };
+        let interrupt_type = if lr.is_set(ICH_LR_EL2::HW) {
+            VirtualInterruptType::Hardware {
+                physical_id: lr.read(ICH_LR_EL2::PINTID) as u32,
+            }
+        } else {
+            VirtualInterruptType::Software {
+                eoi_maintenance: lr.is_set(ICH_LR_EL2::EOI),
+            }
+        };
+        Self {
+            virtual_id: unsafe { IntId::raw(lr.read(ICH_LR_EL2::VINTID) as u32) },
+            priority: lr.read(ICH_LR_EL2::PRIORITY) as u8,
+            state,
+            group: if lr.is_set(ICH_LR_EL2::GROUP) {
+                Group::Group1
+            } else {
+                Group::Group0
+            },
+            interrupt_type,
+        }
+    }
+}
+
+/// Virtual interrupt type for List Register configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VirtualInterruptType {
+    /// Purely virtual interrupt, optionally raising a maintenance interrupt on EOI
+    Software { eoi_maintenance: bool },
+    /// Virtual interrupt backed by a physical interrupt, which is deactivated
+    /// when the guest deactivates the virtual one
+    Hardware { physical_id: u32 },
+}
+
+/// State of a list register entry (`ICH_LR<n>_EL2.State`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VirtualInterruptState {
+    Invalid = 0,
+    Pending = 1,
+    Active = 2,
+    PendingAndActive = 3,
+}
@@ -8,6 +8,9 @@ use crate::test_suit::test_if;
 
 static SGI_INTERRUPT_FIRED: AtomicBool = AtomicBool::new(false);
 static SGI_SEND_CPU: AtomicU64 = AtomicU64::new(0);
+// 通过共享内存传递的消息，发送 SGI 前写入，中断处理中检查
+static SGI_MESSAGE: AtomicU64 = AtomicU64::new(0);
+const SGI_MESSAGE_VALUE: u64 = 0x5A5A_1234;
 
 const SGI_IRQ: IntId = IntId::sgi(1); // 使用SGI 1
 
@@ -36,6 +39,8 @@ pub fn test_to_current_cpu() {
     // 发送SGI到当前CPU
     let cpuid = MPIDR_EL1.get();
     SGI_SEND_CPU.store(cpuid & 0xFFFFFF, Ordering::SeqCst);
+    // 普通写入，由 send_sgi 中的屏障保证在 SGI 到达前可见
+    SGI_MESSAGE.store(SGI_MESSAGE_VALUE, Ordering::Relaxed);
 
     debug!("Sending SGI to current CPU...");
     test_if().sgi_to_current(SGI_IRQ);
@@ -105,6 +110,12 @@ pub fn handle(intid: IntId, from_cpu: Option<usize>) -> Option<()> {
     // 处理PPI中断
     debug!("Handling SGI interrupt");
 
+    let message = SGI_MESSAGE.load(Ordering::Relaxed);
+    assert_eq!(
+        message, SGI_MESSAGE_VALUE,
+        "SGI arrived before the message written ahead of it was visible"
+    );
+
     // 设置标志表示中断已触发
     SGI_INTERRUPT_FIRED.store(true, Ordering::SeqCst);
     None
@@ -79,20 +79,13 @@ fn init_gic() {
 #[somehal::irq_handler]
 fn irq_handler() {
     // debug!("IRQ handler invoked");
-    let ack = trap().ack();
-
-    debug!("Handling IRQ: {ack:?}");
-
-    if handle_list(ack).is_some() {
-        panic!("Unhandled IRQ: {ack:?}");
-    }
-
-    if !ack.is_special() {
-        trap().eoi(ack);
-        if trap().eoi_mode_ns() {
-            trap().dir(ack);
+    trap().dispatch(|ack| {
+        debug!("Handling IRQ: {ack:?}");
+        if handle_list(ack).is_some() {
+            panic!("Unhandled IRQ: {ack:?}");
         }
-    }
+        true
+    });
 }
 
 fn trap() -> &'static TrapOp {
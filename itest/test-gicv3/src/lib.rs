@@ -18,6 +18,7 @@ fn main(_args: &somehal::BootInfo) -> ! {
 
     test_suit::ppi::test_irq();
     test_suit::sgi::test_to_current_cpu();
+    test_group_enable();
 
     info!("{TEST_SUCCESS}");
 }
@@ -46,9 +47,13 @@ fn init_gic() {
 
     let mut gic = unsafe { Gic::new(gicd_base.into(), gicc_base.into()) };
 
-    gic.init();
+    if let Err(e) = gic.init() {
+        panic!("GICv3 distributor init failed: {e}");
+    }
     let mut cpu = gic.cpu_interface();
-    cpu.init_current_cpu().unwrap();
+    if let Err(e) = cpu.init_current_cpu() {
+        panic!("GICv3 CPU interface init failed: {e}");
+    }
     // cpu.set_eoi_mode_ns(false);
     TRAP_OP.call_once(|| cpu.trap_operations());
     *GIC.lock() = gic;
@@ -64,20 +69,41 @@ fn init_gic() {
 #[somehal::irq_handler]
 fn irq_handler() {
     // debug!("IRQ handler invoked");
-    let ack = trap().ack1();
-
-    debug!("Handling IRQ: {ack:?}");
-
-    if handle_list(ack).is_some() {
-        panic!("Unhandled IRQ: {ack:?}");
-    }
-
-    if !ack.is_special() {
-        trap().eoi1(ack);
-        if trap().eoi_mode() {
-            trap().dir(ack);
+    trap().dispatch(|ack| {
+        debug!("Handling IRQ: {ack:?}");
+        if handle_list(ack).is_some() {
+            panic!("Unhandled IRQ: {ack:?}");
         }
+        true
+    });
+}
+
+fn test_group_enable() {
+    // Group 0 registers are not accessible from Non-secure state
+    let groups: &[Group] = if GIC.lock().summary().security_state == SecurityState::NonSecure {
+        &[Group::Group1]
+    } else {
+        &[Group::Group0, Group::Group1]
+    };
+
+    let cpu_if = CPU_IF.lock();
+    let cpu = cpu_if.as_ref().unwrap();
+    for &group in groups {
+        let old = cpu.group_enabled(group);
+        cpu.set_group_enable(group, !old);
+        assert_eq!(
+            cpu.group_enabled(group),
+            !old,
+            "{group:?} enable did not toggle"
+        );
+        cpu.set_group_enable(group, old);
+        assert_eq!(
+            cpu.group_enabled(group),
+            old,
+            "{group:?} enable not restored"
+        );
     }
+    info!("Group enable test passed");
 }
 
 fn trap() -> &'static TrapOp {
@@ -123,6 +149,9 @@ impl test_base::test_suit::TestIf for CpuImpl {
 
     fn sgi_to_current(&self, intid: IntId) {
         let c = CPU_IF.lock();
-        c.as_ref().unwrap().send_sgi(intid, SGITarget::current());
+        c.as_ref()
+            .unwrap()
+            .send_sgi(intid, SGITarget::current())
+            .unwrap();
     }
 }